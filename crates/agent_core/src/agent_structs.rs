@@ -1,5 +1,11 @@
-use std::error::Error;
+use std::{
+    collections::BTreeMap, error::Error, fmt::{self, Display, Formatter}, path::PathBuf, time::Duration
+};
 use async_trait::async_trait;
+use serde::Deserialize;
+use crate::tool_args::{
+    ArgValue, ToolArgs
+};
 
 #[async_trait]
 pub trait Agent {
@@ -8,7 +14,44 @@ pub trait Agent {
 
 #[async_trait]
 pub trait ToolUser {
-    async fn use_tool(&self, name: &str, args: &[String]) -> Result<String, Box<dyn Error>>;
+    async fn use_tool(&self, name: &str, args: &ToolArgs) -> Result<ToolOutput, Box<dyn Error>>;
+}
+
+// the text of a tool and the values it publishes for the later steps
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolOutput {
+    pub text: String,
+    pub values: BTreeMap<String, ArgValue>,
+}
+
+impl ToolOutput {
+    pub fn with(mut self, name: &str, value: ArgValue) -> Self {
+        self.values.insert(name.to_string(), value);
+        self
+    }
+
+    // the text is published as "output"
+    pub fn get(&self, name: &str) -> Option<ArgValue> {
+        match name {
+            "output" => Some(ArgValue::Text(self.text.clone())),
+            _ => self.values.get(name).cloned(),
+        }
+    }
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        ToolOutput {
+            text,
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl Display for ToolOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
 }
 
 pub struct AgentInput {
@@ -29,10 +72,99 @@ pub enum AgentStatus {
 
 pub struct DevOpsAgent {
     pub steps: Vec<Step>,
+    pub repository: Repository,
+    // how many independent steps run at the same time
+    pub max_parallel_steps: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Repository {
+    pub owner: String,
+    pub name: String,
+    // where the downloaded logs and the failure history of this repository are kept
+    pub data_dir: PathBuf,
 }
 
-#[derive(Debug)]
+// where the agent keeps its logs and the data of a single repository
+const LOGS_DIR: &str = "logs";
+const WORKFLOWS_LOG_FILE: &str = "gh_workflows.log";
+
+impl Repository {
+    // a single OWNER/REPO keeps using the logs directory as is
+    pub fn new(owner: &str, name: &str) -> Self {
+        Repository {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            data_dir: PathBuf::from(LOGS_DIR),
+        }
+    }
+
+    // with REPOS and ORG every repository keeps its data in logs/<owner>/<repo>/
+    pub fn namespaced(owner: &str, name: &str) -> Self {
+        Repository {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            data_dir: PathBuf::from(LOGS_DIR).join(owner).join(name),
+        }
+    }
+
+    // "owner/repo", None for anything else
+    pub fn parse_namespaced(full_name: &str) -> Option<Self> {
+        match full_name.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() => Some(Repository::namespaced(owner, name)),
+            _ => None,
+        }
+    }
+
+    // the downloaded logs of the workflow runs
+    pub fn workflows_log(&self) -> PathBuf {
+        self.data_dir.join(WORKFLOWS_LOG_FILE)
+    }
+}
+
+impl Display for Repository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Step {
+    // unique within a pipeline file, the tool name otherwise
+    pub id: String,
     pub name: String,
-    pub args: Vec<String>,
+    pub args: ToolArgs,
+    pub when: Condition,
+    pub timeout: Option<Duration>,
+    // extra attempts after the first one failed
+    pub retries: u32,
+    // run when the step still fails after its retries, before its dependants are cancelled
+    pub on_failure: Vec<Step>,
+    // ids of the steps that have to succeed or be skipped before this one starts
+    pub needs: Vec<String>,
+}
+
+// when a step runs, judged by the latest completed run of every workflow and branch
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    #[default]
+    Always,
+    RunsFailed,
+    RunsPassed,
+}
+
+impl Step {
+    pub fn new(name: &str, args: ToolArgs) -> Self {
+        Step {
+            id: name.to_string(),
+            name: name.to_string(),
+            args,
+            when: Condition::Always,
+            timeout: None,
+            retries: 0,
+            on_failure: Vec::new(),
+            needs: Vec::new(),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+use std::{
+    error::Error, fmt::{self, Display, Formatter}
+};
+use crate::agent_structs::Step;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepState {
+    Waiting,
+    Running,
+    Succeeded,
+    // its condition did not hold, the steps needing it still run unless they take its outputs
+    Skipped,
+    Failed(String),
+    Cancelled(String),
+}
+
+impl Display for StepState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StepState::Waiting => write!(f, "waiting"),
+            StepState::Running => write!(f, "running"),
+            StepState::Succeeded => write!(f, "succeeded"),
+            StepState::Skipped => write!(f, "skipped"),
+            StepState::Failed(error) => write!(f, "failed: {}", error),
+            StepState::Cancelled(reason) => write!(f, "cancelled: {}", reason),
+        }
+    }
+}
+
+// the steps of a pipeline and what each one waits for, the executor starts the ready ones
+// and reports back how they finished
+#[derive(Debug)]
+pub struct StepGraph {
+    ids: Vec<String>,
+    needs: Vec<Vec<usize>>,
+    states: Vec<StepState>,
+}
+
+impl StepGraph {
+    // a step can only need steps before it, so the graph has no cycles. With repeated ids,
+    // as in PIPELINE where the steps are named after their tools, the latest step before it is meant
+    pub fn new(steps: &[Step]) -> Result<Self, Box<dyn Error>> {
+        let mut needs = Vec::new();
+        for (index, step) in steps.iter().enumerate() {
+            let mut step_needs = Vec::new();
+            for id in &step.needs {
+                let Some(needed) = steps[..index].iter().rposition(|earlier| &earlier.id == id) else {
+                    return Err(format!("Step '{}' needs '{}', which is not defined before it", step.id, id).into());
+                };
+                if !step_needs.contains(&needed) {
+                    step_needs.push(needed);
+                }
+            }
+            needs.push(step_needs);
+        }
+
+        Ok(StepGraph {
+            ids: steps.iter().map(|step| step.id.clone()).collect(),
+            needs,
+            states: vec![StepState::Waiting; steps.len()],
+        })
+    }
+
+    // waiting steps whose needs all succeeded or were skipped, in pipeline order
+    pub fn ready(&self) -> Vec<usize> {
+        (0..self.states.len())
+            .filter(|index| self.states[*index] == StepState::Waiting)
+            .filter(|index| self.needs[*index].iter().all(|needed| matches!(self.states[*needed], StepState::Succeeded | StepState::Skipped)))
+            .collect()
+    }
+
+    pub fn start(&mut self, index: usize) {
+        self.states[index] = StepState::Running;
+    }
+
+    // a failed step cancels every step depending on it, directly or not
+    pub fn finish(&mut self, index: usize, state: StepState) {
+        let failed = matches!(state, StepState::Failed(_));
+        self.states[index] = state;
+        if !failed {
+            return;
+        }
+        let mut cancelled = vec![index];
+        for dependant in index + 1..self.states.len() {
+            let Some(cause) = self.needs[dependant].iter().find(|needed| cancelled.contains(needed)) else {
+                continue;
+            };
+            if self.states[dependant] == StepState::Waiting {
+                let reason = match &self.states[*cause] {
+                    StepState::Failed(_) => format!("'{}' failed", self.ids[*cause]),
+                    _ => format!("'{}' was cancelled", self.ids[*cause]),
+                };
+                self.states[dependant] = StepState::Cancelled(reason);
+                cancelled.push(dependant);
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.states.iter().any(|state| matches!(state, StepState::Waiting | StepState::Running))
+    }
+
+    pub fn state(&self, index: usize) -> &StepState {
+        &self.states[index]
+    }
+
+    pub fn id(&self, index: usize) -> &str {
+        &self.ids[index]
+    }
+
+    pub fn failed(&self) -> Vec<&str> {
+        self.ids_in(|state| matches!(state, StepState::Failed(_)))
+    }
+
+    pub fn succeeded(&self) -> Vec<&str> {
+        self.ids_in(|state| *state == StepState::Succeeded)
+    }
+
+    fn ids_in(&self, filter: impl Fn(&StepState) -> bool) -> Vec<&str> {
+        self.ids
+            .iter()
+            .zip(&self.states)
+            .filter(|(_, state)| filter(state))
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+}
+
+// one line per step, e.g. "classify (needs list_workflows): failed: Timed out after 600s"
+impl Display for StepGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, state) in self.states.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.ids[index])?;
+            if !self.needs[index].is_empty() {
+                let needs = self.needs[index].iter().map(|needed| self.ids[*needed].as_str()).collect::<Vec<&str>>();
+                write!(f, " (needs {})", needs.join(", "))?;
+            }
+            write!(f, ": {}", state)?;
+        }
+        Ok(())
+    }
+}
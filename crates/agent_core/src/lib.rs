@@ -1,25 +1,120 @@
-use std::error::Error;
+use std::{
+    error::Error, path::{Path, PathBuf}, time::Duration
+};
 use async_trait::async_trait;
+use futures::{
+    stream::FuturesUnordered, StreamExt
+};
+use tokio::{
+    sync::OnceCell, time::{sleep, timeout}
+};
 use tracing::{
-    error, info
+    error, info, warn
 };
+use tool_executor::process_execution::command::split_command;
 use crate::{agent_structs::{
-    Agent, AgentInput, AgentResult, AgentStatus, DevOpsAgent, Step, ToolUser
-}, wrappers::{analize_agent_logs, analize_gh_workflows_logs, download_workflows_logs, list_workflows}};
+    Agent, AgentInput, AgentResult, AgentStatus, Condition, DevOpsAgent, Repository, Step, ToolOutput, ToolUser
+}, dag::{StepGraph, StepState}, tool_args::{env_or, StepOutputs, ToolArgs}, wrappers::{analize_agent_logs, latest_runs_failed, classify_failures, diff_failed_logs, analize_gh_workflows_logs, analize_workflow_artifacts, download_workflows_logs, git_blame, git_checkout_dir, git_current_branch, git_diff, git_recent_commits, list_workflows, report_github_failures, run_allowed_command, track_durations, track_recurring_failures, RunFilter, AGENT_LOG_FILE, DEFAULT_MAX_ARTIFACT_MB, DEFAULT_MAX_DIFF_LINES, DEFAULT_RECENT_COMMITS, MAX_PROMPT_LOG_BYTES}};
 
 pub mod agent_structs;
+pub mod dag;
+pub mod pipeline;
+pub mod tool_args;
 pub mod wrappers;
 
+// pause between the attempts of a failing step, transient errors rarely clear up right away
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
 impl DevOpsAgent {
-    pub fn new(steps: Vec<Step>) -> Self {
+    pub fn new(steps: Vec<Step>, repository: Repository) -> Self {
         DevOpsAgent {
             steps,
+            repository,
+            max_parallel_steps: env_or("MAX_PARALLEL_STEPS", 4).max(1),
+        }
+    }
+
+    // retries the step after errors and timeouts until its attempts run out,
+    // the arguments are the ones of the step with the references to earlier outputs resolved
+    async fn run_step(&self, step: &Step, args: &ToolArgs) -> Result<ToolOutput, Box<dyn Error>> {
+        let mut attempt = 0;
+        loop {
+            let error = {
+                let result = match step.timeout {
+                    Some(limit) => timeout(limit, self.use_tool(&step.name, args))
+                        .await
+                        .unwrap_or_else(|_| Err(format!("Timed out after {}s", limit.as_secs()).into())),
+                    None => self.use_tool(&step.name, args).await,
+                };
+                match result {
+                    Ok(output) => return Ok(output),
+                    Err(e) if attempt >= step.retries => return Err(e),
+                    Err(e) => e.to_string(),
+                }
+            };
+            attempt += 1;
+            warn!("Step '{}' failed, retrying ({}/{}): {}", step.id, attempt, step.retries, error);
+            sleep(RETRY_DELAY).await;
+        }
+    }
+
+    // checks the condition, runs the step and its failure handlers when it fails. None when the step is skipped,
+    // errors are turned into text so that the future stays Send
+    async fn execute_step(
+        &self, step: &Step, args: Result<ToolArgs, String>, handler_args: Vec<Result<ToolArgs, String>>, runs_failed: &OnceCell<bool>,
+    ) -> Result<Option<ToolOutput>, String> {
+        let error = match self.condition_holds(step.when, runs_failed).await {
+            Ok(true) => match args {
+                Ok(args) => match self.run_step(step, &args).await.map_err(|e| e.to_string()) {
+                    Ok(output) => return Ok(Some(output)),
+                    Err(e) => e,
+                },
+                Err(e) => e,
+            },
+            Ok(false) => {
+                info!("Skipping step '{}', its condition {:?} does not hold", step.id, step.when);
+                return Ok(None);
+            }
+            Err(e) => format!("Could not evaluate the condition: {}", e),
+        };
+
+        error!("Error executing step '{}': {}", step.id, error);
+        for (handler, args) in step.on_failure.iter().zip(handler_args) {
+            let result = match args {
+                Ok(args) => self.run_step(handler, &args).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(output) => info!("Failure handler '{}' of step '{}' executed with output: {}", handler.id, step.id, output),
+                Err(e) => error!("Failure handler '{}' of step '{}' failed: {}", handler.id, step.id, e),
+            }
         }
+        Err(error)
     }
+
+    // the runs are only looked up once per execution of the pipeline, and only if a step depends on them
+    async fn condition_holds(&self, condition: Condition, runs_failed: &OnceCell<bool>) -> Result<bool, String> {
+        if condition == Condition::Always {
+            return Ok(true);
+        }
+        let failed = *runs_failed
+            .get_or_try_init(|| async { latest_runs_failed(&self.repository).await.map_err(|e| e.to_string()) })
+            .await?;
+        Ok(match condition {
+            Condition::RunsFailed => failed,
+            _ => !failed,
+        })
+    }
+}
+
+fn resolve_args(step: &Step, outputs: &StepOutputs) -> Result<ToolArgs, String> {
+    step.args.resolve(&step.name, outputs).map_err(|e| format!("Could not resolve the arguments: {}", e))
 }
 
 #[async_trait]
 impl Agent for DevOpsAgent {
+    // the steps start as soon as the steps they need are done, up to max_parallel_steps at a time.
+    // A failed step cancels the steps depending on it, the independent ones carry on
     async fn handle_input(&mut self, _input: AgentInput) -> AgentResult {
         if self.steps.is_empty() {
             return AgentResult {
@@ -27,40 +122,146 @@ impl Agent for DevOpsAgent {
                 status: AgentStatus::Error("Planning failed".into())
             };
         }
+        let mut graph = match StepGraph::new(&self.steps) {
+            Ok(graph) => graph,
+            Err(e) => {
+                return AgentResult {
+                    output: e.to_string(),
+                    status: AgentStatus::Error("Planning failed".into())
+                };
+            }
+        };
 
-        for step in &self.steps {
-            match self.use_tool(&step.name, &step.args).await {
-                Ok(output) => {
-                    info!("Step '{}' executed successfully with output: {}", step.name, output);
+        let agent = &*self;
+        let runs_failed = OnceCell::new();
+        let mut outputs = StepOutputs::new();
+        let mut running = FuturesUnordered::new();
+        loop {
+            // the arguments are resolved when the step starts, its needs have published their outputs by then
+            for index in graph.ready().into_iter().take(agent.max_parallel_steps.saturating_sub(running.len())) {
+                let step = &agent.steps[index];
+                graph.start(index);
+                info!("Starting step '{}'", step.id);
+                // the needs are all done, a referenced step without outputs was skipped, and so is this one
+                let skipped_reference = step.args.referenced_steps().into_iter().find(|referenced| !outputs.contains_key(referenced));
+                let args = resolve_args(step, &outputs);
+                let handler_args = step.on_failure.iter().map(|handler| resolve_args(handler, &outputs)).collect();
+                let runs_failed = &runs_failed;
+                running.push(async move {
+                    if let Some(skipped) = skipped_reference {
+                        info!("Skipping step '{}', it takes the outputs of '{}', which was skipped", step.id, skipped);
+                        return (index, Ok(None));
+                    }
+                    (index, agent.execute_step(step, args, handler_args, runs_failed).await)
+                });
+            }
+
+            let Some((index, result)) = running.next().await else {
+                break;
+            };
+            let state = match result {
+                Ok(Some(output)) => {
+                    info!("Step '{}' executed successfully with output: {}", graph.id(index), output);
+                    outputs.insert(graph.id(index).to_string(), output);
+                    StepState::Succeeded
                 }
-                Err(e) => {
-                    error!("Error executing step '{}': {}", step.name, e);
-                    return AgentResult {
-                        output: format!("Error executing step '{}': {}", step.name, e),
-                        status: AgentStatus::Error(format!("Step '{}' failed", step.name))
-                    };
+                Ok(None) => {
+                    // with repeated ids, the outputs of an earlier step of the same id are not meant anymore
+                    outputs.remove(graph.id(index));
+                    StepState::Skipped
                 }
+                Err(e) => StepState::Failed(e),
             };
+            graph.finish(index, state);
         }
 
+        info!("Pipeline state:\n{}", graph);
+        let failed = graph.failed();
+        if !failed.is_empty() {
+            return AgentResult {
+                output: format!("Failed steps: {}\n{}", failed.join(", "), graph),
+                status: AgentStatus::Error(format!("Steps failed: {}", failed.join(", ")))
+            };
+        }
+        let executed = graph.succeeded();
         AgentResult {
-            output: format!("Executed {} steps: {:?}", self.steps.len(), self.steps),
+            output: format!("Executed {} steps: {:?}\n{}", executed.len(), executed, graph),
             status: AgentStatus::Success,
         }
     }
 }
 
+// the arguments were validated against the tool when the pipeline was loaded, the ones left out
+// fall back to the environment and then to the defaults
 #[async_trait]
 impl ToolUser for DevOpsAgent {
-    async fn use_tool(&self, name: &str, _args: &[String]) -> Result<String, Box<dyn Error>> {
+    async fn use_tool(&self, name: &str, args: &ToolArgs) -> Result<ToolOutput, Box<dyn Error>> {
+        let filter = RunFilter {
+            branch: args.text("branch").map(String::from),
+            workflow: args.text("workflow").map(String::from),
+            limit: args.integer("limit").map(|limit| limit as usize),
+            run_ids: args.integer_list("run_ids").map(<[u64]>::to_vec),
+        };
+        let max_bytes = args.integer("max_bytes").unwrap_or(MAX_PROMPT_LOG_BYTES);
+        let checkout = args.text("checkout").map(PathBuf::from).or_else(git_checkout_dir);
+
         match name {
-            "download_workflows_logs" => download_workflows_logs().await,
-            "list_workflows" => list_workflows().await,
-            "analize_agent_logs" => analize_agent_logs().await,
-            "analize_gh_workflows_logs" => analize_gh_workflows_logs().await,
+            "download_workflows_logs" => download_workflows_logs(&self.repository, &filter).await,
+            "list_workflows" => list_workflows(&self.repository, &filter).await,
+            "analize_agent_logs" => analize_agent_logs(Path::new(args.text("file").unwrap_or(AGENT_LOG_FILE)), max_bytes).await.map(ToolOutput::from),
+            "analize_gh_workflows_logs" => {
+                analize_gh_workflows_logs(&self.repository, max_bytes, checkout.as_deref(), args.integer_list("run_ids")).await.map(ToolOutput::from)
+            }
+            "analize_workflow_artifacts" => {
+                let max_artifact_mb = args.integer("max_artifact_mb").unwrap_or(DEFAULT_MAX_ARTIFACT_MB);
+                let max_artifact_size = max_artifact_mb.checked_mul(1024 * 1024).ok_or_else(|| format!("max_artifact_mb {} is too large", max_artifact_mb))?;
+                analize_workflow_artifacts(&self.repository, max_artifact_size).await.map(ToolOutput::from)
+            }
+            "report_github_failures" => {
+                let set_status = args.flag("commit_status").unwrap_or_else(|| env_or("GITHUB_COMMIT_STATUS", false));
+                report_github_failures(&self.repository, set_status).await
+            }
+            "track_recurring_failures" => {
+                let threshold = args.integer("threshold").map(|threshold| threshold as usize).unwrap_or_else(|| env_or("ISSUE_THRESHOLD", 3));
+                track_recurring_failures(&self.repository, threshold).await
+            }
+            "classify_failures" => {
+                let auto_retry = args.flag("retry").unwrap_or_else(|| env_or("AUTO_RETRY_FAILURES", false));
+                classify_failures(&self.repository, &filter, auto_retry).await
+            }
+            "diff_failed_logs" => {
+                let max_lines = args.integer("max_lines").map(|lines| lines as usize).unwrap_or(DEFAULT_MAX_DIFF_LINES);
+                diff_failed_logs(&self.repository, &filter, max_lines).await.map(ToolOutput::from)
+            }
+            "track_durations" => {
+                let window_days = args.integer("window_days").map(|days| days as i64).unwrap_or_else(|| env_or("DURATION_WINDOW_DAYS", 14));
+                let threshold = args.number("threshold_percent").unwrap_or_else(|| env_or("DURATION_REGRESSION_PERCENT", 40.0));
+                track_durations(&self.repository, window_days, threshold).await.map(ToolOutput::from)
+            }
+            "run_command" => {
+                let argv = split_command(args.text("command").unwrap_or_default())?;
+                run_allowed_command(&argv).await
+            }
+            "git_current_branch" | "git_recent_commits" | "git_blame" | "git_diff" => {
+                let Some(checkout) = checkout else {
+                    return Err(format!("'{}' needs the checkout argument or GIT_CHECKOUT_DIR", name).into());
+                };
+                match name {
+                    "git_current_branch" => git_current_branch(&checkout).await,
+                    "git_recent_commits" => {
+                        let limit = args.integer("limit").map(|limit| limit as usize).unwrap_or(DEFAULT_RECENT_COMMITS);
+                        git_recent_commits(&checkout, limit).await.map(ToolOutput::from)
+                    }
+                    "git_blame" => {
+                        let line = u32::try_from(args.integer("line").unwrap_or_default())?;
+                        git_blame(&checkout, args.text("file").unwrap_or_default(), line).await.map(ToolOutput::from)
+                    }
+                    _ => git_diff(&checkout, args.text("from").unwrap_or_default(), args.text("to").unwrap_or_default()).await.map(ToolOutput::from),
+                }
+            }
             "notify" => {
                 info!("Using tool 'notify' to send notification");
-                return Ok("Given pipeline has been executed.".into());
+                return Ok(ToolOutput::from(String::from("Given pipeline has been executed.")));
             }
             _ => {
                 error!("Tool '{}' not recognized", name);
@@ -78,7 +279,11 @@ where
         let result = agent.handle_input(input).await;
 
         if let AgentStatus::Success = result.status {
-            let output = agent.use_tool("notify", &[result.output.clone()]).await;
+            let mut args = ToolArgs::default();
+            if let Err(e) = args.set_literal("notify", "message", &result.output) {
+                warn!("The notification is sent without the output: {}", e);
+            }
+            let output = agent.use_tool("notify", &args).await;
             info!("Notification sent with output: {:?}", output);
         }
 }
@@ -0,0 +1,282 @@
+use serde::{
+    de::{DeserializeSeed, Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor}, Deserialize, Deserializer
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet}, error::Error, fmt::{self, Display, Formatter}, fs, path::Path, time::Duration
+};
+use crate::{
+    agent_structs::{
+        Condition, Step
+    },
+    tool_args::{
+        tool_spec, ToolArgs
+    }
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PipelineFormat {
+    Yaml,
+    Toml,
+}
+
+impl PipelineFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(PipelineFormat::Yaml),
+            "toml" => Some(PipelineFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineFile {
+    steps: Vec<StepDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepDefinition {
+    id: Option<String>,
+    tool: String,
+    #[serde(default)]
+    args: BTreeMap<String, ArgLiteral>,
+    #[serde(default)]
+    when: Condition,
+    timeout_secs: Option<u64>,
+    #[serde(default)]
+    retries: u32,
+    #[serde(default)]
+    on_failure: Vec<StepDefinition>,
+    // the step before it when left out, an empty list starts the step right away
+    needs: Option<Vec<String>>,
+}
+
+// argument values as written in the file, they are checked against the tool afterwards
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArgLiteral {
+    Flag(bool),
+    Integer(i64),
+    Number(f64),
+    Text(String),
+}
+
+impl Display for ArgLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgLiteral::Flag(value) => write!(f, "{}", value),
+            ArgLiteral::Integer(value) => write!(f, "{}", value),
+            ArgLiteral::Number(value) => write!(f, "{}", value),
+            ArgLiteral::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+pub fn load_pipeline_file(path: &Path) -> Result<Vec<Step>, Box<dyn Error>> {
+    let format = PipelineFormat::from_path(path).ok_or_else(|| format!("{}: pipeline files end with .yaml, .yml or .toml", path.display()))?;
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_pipeline_file(&content, format).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+// syntax and type errors are located by the parsers, so are unknown tools and arguments, by walking the file again
+pub fn parse_pipeline_file(content: &str, format: PipelineFormat) -> Result<Vec<Step>, Box<dyn Error>> {
+    let file: PipelineFile = match format {
+        PipelineFormat::Yaml => serde_yaml::from_str(content)?,
+        PipelineFormat::Toml => toml::from_str(content)?,
+    };
+    if file.steps.is_empty() {
+        return Err("The pipeline has no steps".into());
+    }
+
+    let mut ids = HashSet::new();
+    // tools of the steps so far by id, the ones a step can refer to and need
+    let mut earlier_steps = HashMap::new();
+    let mut previous = None;
+    file.steps
+        .into_iter()
+        .enumerate()
+        .map(|(index, definition)| {
+            let sequential = definition.needs.is_none();
+            let path = vec![Segment::Key(String::from("steps")), Segment::Index(index)];
+            let default_id = definition.tool.clone();
+            let mut step = convert_step(definition, &format!("Step {}", index + 1), default_id, path, &mut ids, &earlier_steps)
+                .map_err(|e| locate(content, format, e))?;
+            if sequential && let Some(previous) = previous.take() && !step.needs.contains(&previous) {
+                step.needs.insert(0, previous);
+            }
+            earlier_steps.insert(step.id.clone(), step.name.clone());
+            previous = Some(step.id.clone());
+            Ok(step)
+        })
+        .collect()
+}
+
+// where a value is in the file, e.g. steps, 1, args, retry
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+// an error about the value at the path, or about its closest parent that is there
+struct StepError {
+    path: Vec<Segment>,
+    message: String,
+}
+
+// ids are unique among the steps of the pipeline, and among the handlers of a step
+fn convert_step(
+    definition: StepDefinition, label: &str, default_id: String, path: Vec<Segment>, ids: &mut HashSet<String>,
+    earlier_steps: &HashMap<String, String>,
+) -> Result<Step, StepError> {
+    let label = format!("{} '{}'", label, definition.id.as_deref().unwrap_or(&definition.tool));
+    let fail = |keys: &[&str], message: String| -> StepError {
+        let mut path = path.clone();
+        path.extend(keys.iter().map(|key| Segment::Key(key.to_string())));
+        StepError {
+            path,
+            message: format!("{}: {}", label, message),
+        }
+    };
+
+    if tool_spec(&definition.tool).is_none() {
+        return Err(fail(&["tool"], format!("Tool '{}' not recognized", definition.tool)));
+    }
+    let mut args = ToolArgs::default();
+    for (key, value) in &definition.args {
+        args.set(&definition.tool, key, &value.to_string()).map_err(|e| fail(&["args", key], e.to_string()))?;
+    }
+    args.check_required(&definition.tool).map_err(|e| fail(&[], e.to_string()))?;
+    args.check_references(&definition.tool, earlier_steps).map_err(|(key, e)| fail(&["args", &key], e.to_string()))?;
+
+    let id = match definition.id {
+        Some(id) if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') => {
+            return Err(fail(&["id"], format!("Invalid id '{}', use letters, digits, '_' and '-'", id)));
+        }
+        Some(id) => id,
+        None => default_id,
+    };
+    if !ids.insert(id.clone()) {
+        return Err(fail(&["id"], format!("The id '{}' is already used by an earlier step, give the steps distinct ids", id)));
+    }
+    // a step also waits for the steps whose outputs it takes
+    let is_handler = path.iter().any(|segment| matches!(segment, Segment::Key(key) if key == "on_failure"));
+    let mut needs = match definition.needs {
+        Some(_) if is_handler => {
+            return Err(fail(&["needs"], String::from("on_failure steps run right after their step, they cannot have needs")));
+        }
+        Some(needs) => needs,
+        None => Vec::new(),
+    };
+    if let Some(unknown) = needs.iter().find(|needed| !earlier_steps.contains_key(*needed)) {
+        return Err(fail(&["needs"], format!("Needs '{}', which is not defined before it", unknown)));
+    }
+    for referenced in args.referenced_steps() {
+        if !needs.contains(&referenced) {
+            needs.push(referenced);
+        }
+    }
+    if definition.timeout_secs == Some(0) {
+        return Err(fail(&["timeout_secs"], String::from("timeout_secs must be greater than 0")));
+    }
+
+    // handlers without an id are named after their step, e.g. classify.on_failure.1
+    let mut handler_ids = HashSet::new();
+    let on_failure = definition.on_failure
+        .into_iter()
+        .enumerate()
+        .map(|(index, handler)| {
+            // a handler can refer to the steps before its parent, the ones that succeeded
+            let mut handler_path = path.clone();
+            handler_path.extend([Segment::Key(String::from("on_failure")), Segment::Index(index)]);
+            let default_id = format!("{}.on_failure.{}", id, index + 1);
+            convert_step(handler, &format!("{}, on_failure step {}", label, index + 1), default_id, handler_path, &mut handler_ids, earlier_steps)
+        })
+        .collect::<Result<Vec<Step>, StepError>>()?;
+    // the arguments of the handlers are taken when the step starts, so it waits for the steps they refer to as well
+    for referenced in on_failure.iter().flat_map(|handler| &handler.needs) {
+        if !needs.contains(referenced) {
+            needs.push(referenced.clone());
+        }
+    }
+
+    Ok(Step {
+        id,
+        name: definition.tool,
+        args,
+        when: definition.when,
+        timeout: definition.timeout_secs.map(Duration::from_secs),
+        retries: definition.retries,
+        on_failure,
+        needs,
+    })
+}
+
+// the parsers know the position of every value, so the file is parsed once more and made to fail at the value
+// the error is about, a value left out of the file is reported at its parent
+fn locate(content: &str, format: PipelineFormat, error: StepError) -> Box<dyn Error> {
+    let mut path = error.path.as_slice();
+    loop {
+        let line = match format {
+            PipelineFormat::Yaml => Probe(path).deserialize(serde_yaml::Deserializer::from_str(content)).err().and_then(|e| e.location()).map(|location| location.line()),
+            PipelineFormat::Toml => Probe(path).deserialize(toml::Deserializer::new(content)).err().and_then(|e| e.span()).map(|span| {
+                content.as_bytes()[..span.start.min(content.len())].iter().filter(|byte| **byte == b'\n').count() + 1
+            }),
+        };
+        match (line, path.split_last()) {
+            (Some(line), _) => return format!("line {}: {}", line, error.message).into(),
+            (None, Some((_, parent))) => path = parent,
+            (None, None) => return error.message.into(),
+        }
+    }
+}
+
+// goes down the path and fails once it is reached, or on a plain value found on the way
+struct Probe<'a>(&'a [Segment]);
+
+impl<'de> DeserializeSeed<'de> for Probe<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Probe<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a table or a list")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some((segment, rest)) = self.0.split_first() else {
+            return Err(A::Error::custom("reached"));
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            if matches!(segment, Segment::Key(wanted) if *wanted == key) {
+                return map.next_value_seed(Probe(rest));
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let Some((segment, rest)) = self.0.split_first() else {
+            return Err(A::Error::custom("reached"));
+        };
+        for index in 0.. {
+            let item = match segment {
+                Segment::Index(wanted) if *wanted == index => seq.next_element_seed(Probe(rest))?,
+                _ => seq.next_element::<IgnoredAny>()?.map(|_| ()),
+            };
+            if item.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
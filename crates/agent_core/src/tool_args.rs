@@ -0,0 +1,426 @@
+use std::{
+    collections::{BTreeMap, HashMap}, env::var, error::Error, fmt::{self, Display, Formatter}, str::FromStr
+};
+use crate::agent_structs::{
+    Step, ToolOutput
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Text,
+    Integer,
+    Number,
+    Flag,
+    // comma-separated, e.g. "101,102"
+    IntegerList,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Text(String),
+    Integer(u64),
+    Number(f64),
+    Flag(bool),
+    IntegerList(Vec<u64>),
+}
+
+impl Display for ArgKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ArgKind::Text => "a text",
+            ArgKind::Integer => "a non-negative integer",
+            ArgKind::Number => "a number",
+            ArgKind::Flag => "true or false",
+            ArgKind::IntegerList => "a comma-separated list of integers",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+// the form references are substituted with and arguments are parsed from
+impl Display for ArgValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgValue::Text(value) => write!(f, "{}", value),
+            ArgValue::Integer(value) => write!(f, "{}", value),
+            ArgValue::Number(value) => write!(f, "{}", value),
+            ArgValue::Flag(value) => write!(f, "{}", value),
+            ArgValue::IntegerList(values) => write!(f, "{}", values.iter().map(u64::to_string).collect::<Vec<String>>().join(",")),
+        }
+    }
+}
+
+impl ArgKind {
+    // an output of this kind can be passed to an argument of the other kind
+    fn fits(self, argument: ArgKind) -> bool {
+        self == argument || argument == ArgKind::Text || (self == ArgKind::Integer && argument == ArgKind::Number)
+    }
+}
+
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+}
+
+// named values a tool publishes for the later steps, besides its text "output" every tool has
+pub struct OutputSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+}
+
+pub struct ToolSpec {
+    pub name: &'static str,
+    pub args: &'static [ArgSpec],
+    pub outputs: &'static [OutputSpec],
+}
+
+impl ToolSpec {
+    pub fn output_kind(&self, name: &str) -> Option<ArgKind> {
+        if name == "output" {
+            return Some(ArgKind::Text);
+        }
+        self.outputs.iter().find(|output| output.name == name).map(|output| output.kind)
+    }
+}
+
+const fn optional(name: &'static str, kind: ArgKind) -> ArgSpec {
+    ArgSpec { name, kind, required: false }
+}
+
+const fn required(name: &'static str, kind: ArgKind) -> ArgSpec {
+    ArgSpec { name, kind, required: true }
+}
+
+// filters of the tools going through the runs of the CI provider
+const BRANCH: ArgSpec = optional("branch", ArgKind::Text);
+const WORKFLOW: ArgSpec = optional("workflow", ArgKind::Text);
+const LIMIT: ArgSpec = optional("limit", ArgKind::Integer);
+const RUN_IDS: ArgSpec = optional("run_ids", ArgKind::IntegerList);
+// the local checkout, GIT_CHECKOUT_DIR by default
+const CHECKOUT: ArgSpec = optional("checkout", ArgKind::Text);
+
+const fn output(name: &'static str, kind: ArgKind) -> OutputSpec {
+    OutputSpec { name, kind }
+}
+
+const RUN_IDS_OUTPUT: OutputSpec = output("run_ids", ArgKind::IntegerList);
+
+pub const TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        name: "list_workflows",
+        args: &[BRANCH, WORKFLOW, LIMIT, RUN_IDS],
+        outputs: &[RUN_IDS_OUTPUT, output("failed_run_ids", ArgKind::IntegerList)],
+    },
+    ToolSpec { name: "download_workflows_logs", args: &[BRANCH, WORKFLOW, LIMIT, RUN_IDS], outputs: &[RUN_IDS_OUTPUT] },
+    ToolSpec { name: "analize_agent_logs", args: &[optional("file", ArgKind::Text), optional("max_bytes", ArgKind::Integer)], outputs: &[] },
+    // with run_ids the logs of these runs are analized instead of the downloaded ones
+    ToolSpec { name: "analize_gh_workflows_logs", args: &[CHECKOUT, optional("max_bytes", ArgKind::Integer), RUN_IDS], outputs: &[] },
+    ToolSpec { name: "analize_workflow_artifacts", args: &[optional("max_artifact_mb", ArgKind::Integer)], outputs: &[] },
+    ToolSpec { name: "report_github_failures", args: &[optional("commit_status", ArgKind::Flag)], outputs: &[RUN_IDS_OUTPUT] },
+    ToolSpec { name: "track_recurring_failures", args: &[optional("threshold", ArgKind::Integer)], outputs: &[output("issue_numbers", ArgKind::IntegerList)] },
+    ToolSpec {
+        name: "classify_failures",
+        args: &[BRANCH, WORKFLOW, LIMIT, RUN_IDS, optional("retry", ArgKind::Flag)],
+        outputs: &[RUN_IDS_OUTPUT, output("retried_run_ids", ArgKind::IntegerList)],
+    },
+    ToolSpec { name: "diff_failed_logs", args: &[BRANCH, WORKFLOW, LIMIT, RUN_IDS, optional("max_lines", ArgKind::Integer)], outputs: &[] },
+    ToolSpec { name: "track_durations", args: &[optional("window_days", ArgKind::Integer), optional("threshold_percent", ArgKind::Number)], outputs: &[] },
+    ToolSpec { name: "run_command", args: &[required("command", ArgKind::Text)], outputs: &[output("stdout", ArgKind::Text)] },
+    ToolSpec { name: "git_current_branch", args: &[CHECKOUT], outputs: &[output("branch", ArgKind::Text)] },
+    ToolSpec { name: "git_recent_commits", args: &[CHECKOUT, LIMIT], outputs: &[] },
+    ToolSpec { name: "git_blame", args: &[CHECKOUT, required("file", ArgKind::Text), required("line", ArgKind::Integer)], outputs: &[] },
+    ToolSpec { name: "git_diff", args: &[CHECKOUT, required("from", ArgKind::Text), required("to", ArgKind::Text)], outputs: &[] },
+    ToolSpec { name: "notify", args: &[optional("message", ArgKind::Text)], outputs: &[] },
+];
+
+pub fn tool_spec(name: &str) -> Option<&'static ToolSpec> {
+    TOOLS.iter().find(|tool| tool.name == name)
+}
+
+// validated arguments of a step, the tools read them by name.
+// Arguments referring to the outputs of earlier steps, e.g. "${steps.download.run_ids}", are kept as written
+// until the step runs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolArgs {
+    values: BTreeMap<String, ArgValue>,
+    templates: BTreeMap<String, String>,
+}
+
+// outputs of the steps that ran so far, by step id
+pub type StepOutputs = HashMap<String, ToolOutput>;
+
+impl ToolArgs {
+    // "key=value" pairs, checked against the arguments the tool accepts
+    pub fn parse(tool: &str, args: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut parsed = ToolArgs::default();
+        for arg in args {
+            let Some((key, value)) = arg.split_once('=') else {
+                return Err(format!("Argument '{}' of tool '{}' is not in key=value form", arg, tool).into());
+            };
+            parsed.set(tool, key, value)?;
+        }
+        parsed.check_required(tool)?;
+
+        Ok(parsed)
+    }
+
+    pub fn set(&mut self, tool: &str, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let arg_spec = self.unset_arg(tool, key)?;
+        if value.contains("${") {
+            references(value)?;
+            self.templates.insert(key.to_string(), value.to_string());
+        } else {
+            self.values.insert(key.to_string(), parse_value(arg_spec, value)?);
+        }
+        Ok(())
+    }
+
+    // the value as it is, without looking for references, e.g. the output of a run passed on to notify
+    pub fn set_literal(&mut self, tool: &str, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let arg_spec = self.unset_arg(tool, key)?;
+        self.values.insert(key.to_string(), parse_value(arg_spec, value)?);
+        Ok(())
+    }
+
+    fn unset_arg(&self, tool: &str, key: &str) -> Result<&'static ArgSpec, Box<dyn Error>> {
+        let spec = tool_spec(tool).ok_or_else(|| format!("Tool '{}' not recognized", tool))?;
+        let Some(arg_spec) = spec.args.iter().find(|arg_spec| arg_spec.name == key) else {
+            let expected = spec.args.iter().map(|arg_spec| arg_spec.name).collect::<Vec<&str>>();
+            return Err(format!("Unknown argument '{}' of tool '{}', expected one of: {}", key, tool, expected.join(", ")).into());
+        };
+        if self.values.contains_key(key) || self.templates.contains_key(key) {
+            return Err(format!("Argument '{}' of tool '{}' is given twice", key, tool).into());
+        }
+        Ok(arg_spec)
+    }
+
+    pub fn check_required(&self, tool: &str) -> Result<(), Box<dyn Error>> {
+        let spec = tool_spec(tool).ok_or_else(|| format!("Tool '{}' not recognized", tool))?;
+        let given = |name: &str| self.values.contains_key(name) || self.templates.contains_key(name);
+        match spec.args.iter().find(|arg_spec| arg_spec.required && !given(arg_spec.name)) {
+            Some(missing) => Err(format!("Tool '{}' requires the '{}' argument", tool, missing.name).into()),
+            None => Ok(()),
+        }
+    }
+
+    // the references have to name an earlier step, given with its tool, and an output of that tool
+    // fitting the argument; a reference inside a longer value is only allowed in text arguments
+    pub fn check_references(&self, tool: &str, earlier_steps: &HashMap<String, String>) -> Result<(), (String, Box<dyn Error>)> {
+        let Some(spec) = tool_spec(tool) else {
+            return Ok(());
+        };
+        for (key, template) in &self.templates {
+            let kind = spec.args.iter().find(|arg_spec| arg_spec.name == key).map_or(ArgKind::Text, |arg_spec| arg_spec.kind);
+            let fail = |message: String| (key.clone(), message.into());
+            let refs = references(template).map_err(|e| fail(e.to_string()))?;
+            let whole = refs.len() == 1 && template.trim() == format!("${{steps.{}.{}}}", refs[0].0, refs[0].1);
+
+            for (step, output) in refs {
+                let Some(step_tool) = earlier_steps.get(&step) else {
+                    return Err(fail(format!("Argument '{}' refers to step '{}', which does not run before it", key, step)));
+                };
+                let Some(output_kind) = tool_spec(step_tool).and_then(|spec| spec.output_kind(&output)) else {
+                    return Err(fail(format!("Argument '{}' refers to '{}', which step '{}' ({}) does not publish", key, output, step, step_tool)));
+                };
+                if !(whole && output_kind.fits(kind)) && kind != ArgKind::Text {
+                    return Err(fail(format!("Argument '{}' expects {}, but steps.{}.{} is {}", key, kind, step, output, output_kind)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the arguments with the references replaced by the outputs of the steps that ran
+    pub fn resolve(&self, tool: &str, outputs: &StepOutputs) -> Result<ToolArgs, Box<dyn Error>> {
+        let spec = tool_spec(tool).ok_or_else(|| format!("Tool '{}' not recognized", tool))?;
+        let mut resolved = ToolArgs {
+            values: self.values.clone(),
+            templates: BTreeMap::new(),
+        };
+        for (key, template) in &self.templates {
+            let mut value = template.clone();
+            for (step, output) in references(template)? {
+                let published = outputs
+                    .get(&step)
+                    .ok_or_else(|| format!("Argument '{}' refers to step '{}', which did not run", key, step))?
+                    .get(&output)
+                    .ok_or_else(|| format!("Step '{}' did not publish '{}'", step, output))?;
+                value = value.replace(&format!("${{steps.{}.{}}}", step, output), &published.to_string());
+            }
+            let arg_spec = spec.args.iter().find(|arg_spec| arg_spec.name == key).ok_or_else(|| format!("Unknown argument '{}'", key))?;
+            resolved.values.insert(key.clone(), parse_value(arg_spec, &value)?);
+        }
+        Ok(resolved)
+    }
+
+    // ids of the steps the arguments refer to, the step has to wait for them
+    pub fn referenced_steps(&self) -> Vec<String> {
+        let mut steps = self.templates
+            .values()
+            .flat_map(|template| references(template).unwrap_or_default())
+            .map(|(step, _)| step)
+            .collect::<Vec<String>>();
+        steps.sort();
+        steps.dedup();
+        steps
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.values.get(name) {
+            Some(ArgValue::Text(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, name: &str) -> Option<u64> {
+        match self.values.get(name) {
+            Some(ArgValue::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, name: &str) -> Option<f64> {
+        match self.values.get(name) {
+            Some(ArgValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        match self.values.get(name) {
+            Some(ArgValue::Flag(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn integer_list(&self, name: &str) -> Option<&[u64]> {
+        match self.values.get(name) {
+            Some(ArgValue::IntegerList(values)) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+// the (step id, output name) pairs of the "${steps.<id>.<output>}" references in the value
+pub fn references(value: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut found = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}').ok_or_else(|| format!("Unterminated reference in '{}'", value))?;
+        let reference = &rest[start + 2..start + end];
+        let parts = reference.split('.').collect::<Vec<&str>>();
+        match parts.as_slice() {
+            ["steps", step, output] if !step.is_empty() && !output.is_empty() => found.push((step.to_string(), output.to_string())),
+            _ => return Err(format!("Invalid reference '${{{}}}', expected ${{steps.<id>.<output>}}", reference).into()),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(found)
+}
+
+fn parse_value(spec: &ArgSpec, value: &str) -> Result<ArgValue, Box<dyn Error>> {
+    let parsed = match spec.kind {
+        ArgKind::Text => Some(ArgValue::Text(value.to_string())),
+        ArgKind::Integer => value.parse().ok().map(ArgValue::Integer),
+        ArgKind::Number => value.parse().ok().filter(|value: &f64| value.is_finite()).map(ArgValue::Number),
+        ArgKind::Flag => value.parse().ok().map(ArgValue::Flag),
+        ArgKind::IntegerList => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse().ok())
+            .collect::<Option<Vec<u64>>>()
+            .map(ArgValue::IntegerList),
+    };
+    parsed.ok_or_else(|| format!("Invalid value '{}' of argument '{}', expected {}", value, spec.name, spec.kind).into())
+}
+
+// steps are separated by whitespace and followed by their key=value arguments,
+// values with spaces are quoted, e.g. `list_workflows branch=main limit=20 run_command command="cargo test"`
+pub fn parse_pipeline(pipeline: &str) -> Result<Vec<Step>, Box<dyn Error>> {
+    let mut steps: Vec<(String, Vec<String>)> = Vec::new();
+    for token in split_tokens(pipeline)? {
+        let is_arg = token.find('=').is_some_and(|eq| !token[..eq].contains('"'));
+        match steps.last_mut() {
+            Some((_, args)) if is_arg => {
+                let (key, value) = token.split_once('=').unwrap_or_default();
+                args.push(format!("{}={}", key, unquote(value)));
+            }
+            None if is_arg => return Err(format!("Argument '{}' is not preceded by a step", token).into()),
+            _ => steps.push((token, Vec::new())),
+        }
+    }
+
+    // the steps are named after their tools, so a reference points at the latest step of that tool before it.
+    // Every step waits for the one before it
+    let mut earlier_steps = HashMap::new();
+    let mut previous: Option<String> = None;
+    steps
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, args))| {
+            let args = ToolArgs::parse(&name, &args).map_err(|e| format!("Step {} '{}': {}", index + 1, name, e))?;
+            args.check_references(&name, &earlier_steps).map_err(|(_, e)| format!("Step {} '{}': {}", index + 1, name, e))?;
+            earlier_steps.insert(name.clone(), name.clone());
+            let mut step = Step::new(&name, args);
+            step.needs = previous.replace(name).into_iter().collect();
+            Ok(step)
+        })
+        .collect()
+}
+
+// whitespace inside double quotes does not split, a backslash inside them escapes the next character,
+// e.g. command="git commit -m \"fix it\""
+fn split_tokens(pipeline: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut chars = pipeline.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                token.push(c);
+            }
+            '\\' if quoted => {
+                token.push(c);
+                token.extend(chars.next());
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quote in the pipeline".into());
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// only the quotes enclosing the whole value are removed, and the escapes inside them resolved
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+// value of a numeric environment variable, the default when it is not set or not a number
+pub fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    var(name).ok().and_then(|val| val.parse().ok()).unwrap_or(default)
+}
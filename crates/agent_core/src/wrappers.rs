@@ -1,85 +1,801 @@
-use llm::request_llm;
+use llm::{
+    llm_configured, request_llm
+};
+use memory_store::{
+    duration_history::DurationHistory,
+    failure_signatures::{
+        FailureSignatures, SignatureRecord
+    },
+    test_history::TestHistory
+};
 use tool_executor::{
-    github_interaction::github_api_client::{
-        download_workflow_logs, get_github_env_data, list_workflow_runs
-    }, process_execution::read_file
+    ci_providers::{
+        append_run_logs, get_ci_provider, CiRun
+    },
+    error_extraction::{
+        extract_errors, format_excerpts
+    },
+    failure_classification::{
+        classify_failure, test_outcomes
+    },
+    github_interaction::{
+        github_api_client::{
+            fetch_workflow_logs, list_workflow_runs
+        },
+        github_artifacts::{
+            download_artifact, list_run_artifacts, ArtifactRefused
+        },
+        github_commits::{
+            compare_commits, find_regression_range, format_change_context, get_commit
+        },
+        github_error::GithubError,
+        github_http_client::{
+            shared_client, GithubClient
+        },
+        github_issues::{
+            comment_on_issue, create_issue, failure_signature, find_open_issue, signature_label, step_signature
+        },
+        github_jobs::{
+            duration_secs, failed_step_names, list_run_jobs
+        },
+        github_reporting::{
+            find_analysis_comment, find_pull_request_number, format_analysis_comment, run_marker, runs_to_report, set_commit_status, upsert_pr_comment
+        },
+        github_structs::WorkflowRun
+    },
+    local_git::{
+        blame_line, current_branch, diff_refs, recent_commits, source_locations
+    },
+    log_diff::{
+        diff_logs, format_hunks
+    },
+    log_parser::{
+        clean_log, strip_ansi
+    },
+    log_reader::{
+        read_log, LogSlice
+    },
+    process_execution::command::{
+        run_command, CommandPolicy
+    }, report_files::summarize_report, test_reports::parse_test_output
+};
+use tokio::sync::{
+    Mutex, OwnedMutexGuard
 };
 use tracing::{
-    error, info
+    info, warn
 };
 use std::{
-    error::Error, fs::OpenOptions, path::PathBuf
+    collections::{HashMap, HashSet}, env::var, error::Error, fs::{create_dir_all, File, OpenOptions}, path::{Path, PathBuf}, slice, sync::{Arc, LazyLock}
+};
+use crate::{
+    agent_structs::{
+        Repository, ToolOutput
+    },
+    tool_args::ArgValue
 };
 
+// relative to the data directory of the repository
+const FAILURE_SIGNATURES_FILE: &str = "memory/failure_signatures.json";
+const TEST_HISTORY_FILE: &str = "memory/test_history.json";
+const DURATION_HISTORY_FILE: &str = "memory/duration_history.json";
+const ARTIFACTS_DIR: &str = "artifacts";
+const INSPECTED_MARKER: &str = ".inspected";
+// source lines mentioned in the logs that are blamed for the triage prompt
+const MAX_BLAMED_LINES: usize = 10;
+// defaults of the tool arguments
+pub const AGENT_LOG_FILE: &str = "logs/agent.log";
+pub const DEFAULT_MAX_ARTIFACT_MB: u64 = 50;
+pub const DEFAULT_RECENT_COMMITS: usize = 10;
+// divergent lines sent to the llm per failed run
+pub const DEFAULT_MAX_DIFF_LINES: usize = 400;
+// only the end of a log is sent to the llm, that is where builds fail
+pub const MAX_PROMPT_LOG_BYTES: u64 = 256 * 1024;
+const LOG_DIFF_SYSTEM_PROMPT: &str = "You are a helpful assistant that explains CI failures. You are given the lines of a failed run's log that do not appear in the log of the last successful run of the same workflow and branch. Explain what went differently and the most likely cause of the failure. Should not be too long, so human could read them in just 1 minute, and structure your respond with bullet points";
 const SYSTEM_PROMPT: &str = "You are a helpful assistant that analizes and summarizes log files to human understandable format. You need to highlight any errors or warnings found in the logs. Should not be too long, so human could read them in just 1 minute, and structure your respond with bullet points";
+const TRIAGE_SYSTEM_PROMPT: &str = "You are a helpful assistant that analizes and summarizes log files to human understandable format. You need to highlight any errors or warnings found in the logs. The logs are preceded by the commits and changed files since the workflow last passed, point at the commit that most likely caused the failure and explain why. Should not be too long, so human could read them in just 1 minute, and structure your respond with bullet points";
+
+// which runs of the CI provider a tool goes through, all of them by default
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    pub branch: Option<String>,
+    pub workflow: Option<String>,
+    // the newest runs matching the filter
+    pub limit: Option<usize>,
+    // usually the run_ids published by an earlier step
+    pub run_ids: Option<Vec<u64>>,
+}
+
+impl RunFilter {
+    pub fn apply(&self, runs: Vec<CiRun>) -> Vec<CiRun> {
+        runs.into_iter()
+            .filter(|run| self.branch.is_none() || run.branch == self.branch)
+            .filter(|run| self.workflow.is_none() || run.name == self.workflow)
+            .filter(|run| self.run_ids.as_ref().is_none_or(|run_ids| run_ids.contains(&run.id)))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+// the stores are loaded, updated and saved back by tools that can run at the same time, in parallel steps or
+// in the agents of webhook and scheduled runs of the same repository, so each tool holds the locks of the files
+// it writes until it is done with them. The locks are taken in path order, two tools never wait for each other
+async fn lock_files(paths: &[&Path]) -> Vec<OwnedMutexGuard<()>> {
+    static LOCKS: LazyLock<std::sync::Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = LazyLock::new(Default::default);
+
+    let mut paths = paths.to_vec();
+    paths.sort();
+    let mut guards = Vec::new();
+    for path in paths {
+        let lock = LOCKS.lock().unwrap_or_else(|e| e.into_inner()).entry(path.to_path_buf()).or_default().clone();
+        guards.push(lock.lock_owned().await);
+    }
+    guards
+}
+
+pub async fn download_workflows_logs(repository: &Repository, filter: &RunFilter) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'download_workflows_logs' to download workflow logs");
+
+    let provider = get_ci_provider()?;
+    let project = repository.to_string();
+    let log_file = repository.workflows_log();
+    let _locks = lock_files(&[&log_file]).await;
+    let runs = filter.apply(provider.list_runs(&project).await?);
+                
+    for run in &runs {
+        let logs = provider.fetch_run_logs(&project, run.id).await?;
+        append_run_logs(&log_file, run.id, &logs).await?;
+        info!("Downloaded logs for workflow run ID: {}", run.id);
+    }
+    let workflows_ids = runs.iter().map(|run| run.id).collect::<Vec<u64>>();
+    info!("Downloaded logs for workflow run IDs: {:?}", workflows_ids);
+
+    let output = ToolOutput::from(format!("Downloaded logs for workflow run IDs: {:?}", workflows_ids));
+    Ok(output.with("run_ids", ArgValue::IntegerList(workflows_ids)))
+}
+
+pub async fn list_workflows(repository: &Repository, filter: &RunFilter) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'list_workflows' to get workflow runs");
+
+    let provider = get_ci_provider()?;
+    let runs = filter.apply(provider.list_runs(&repository.to_string()).await?);
+                
+    let mut output = String::new();
+    for run in &runs {
+        output.push_str(&format!("ID: {}, Status: {}, Conclusion: {:?}\n", run.id, run.status, run.conclusion));
+    }
+
+    info!("Retrieved {} workflow runs from {}", runs.len(), provider.name());
+
+    let failed = runs.iter().filter(|run| run.conclusion.as_deref() == Some("failure")).map(|run| run.id).collect();
+    Ok(ToolOutput::from(output)
+        .with("run_ids", ArgValue::IntegerList(runs.iter().map(|run| run.id).collect()))
+        .with("failed_run_ids", ArgValue::IntegerList(failed)))
+}
 
-pub async fn download_workflows_logs() -> Result<String, Box<dyn Error>> {
-    match get_github_env_data() {
-        Some(data) => {
-            info!("Using tool 'download_workflows_logs' to download GitHub workflow logs");
-
-            let (token, owner, repo) = (&data[0], &data[1], &data[2]);
-            let response = list_workflow_runs(owner, repo, &token).await?;
-                        
-            for workflow_run in &response.workflow_runs {
-                download_workflow_logs(owner, repo, workflow_run.id, &token).await?;
-                info!("Downloaded logs for workflow run ID: {}", workflow_run.id);
+// whether the latest completed run of some workflow and branch failed
+pub async fn latest_runs_failed(repository: &Repository) -> Result<bool, Box<dyn Error>> {
+    let provider = get_ci_provider()?;
+    let runs = provider.list_runs(&repository.to_string()).await?;
+
+    // runs come newest first
+    let mut seen = HashSet::new();
+    Ok(runs
+        .iter()
+        .filter(|run| run.status == "completed" && seen.insert((run.name.clone(), run.branch.clone())))
+        .any(|run| matches!(run.conclusion.as_deref(), Some("failure") | Some("timed_out"))))
+}
+
+// only the last max_bytes of the log are analized
+pub async fn analize_agent_logs(file: &Path, max_bytes: u64) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'analize_agent_logs' to analize agent log file");
+
+    let prompt = strip_ansi(&read_log(file.to_path_buf(), LogSlice::TailBytes(max_bytes)).await?);
+
+    let respond = request_llm(&prompt, SYSTEM_PROMPT).await?;
+
+    Ok(respond)
+}
+
+// the downloaded logs are analized, or the logs of the given runs, e.g. the ones an earlier step downloaded
+pub async fn analize_gh_workflows_logs(repository: &Repository, max_bytes: u64, checkout: Option<&Path>, run_ids: Option<&[u64]>) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'analize_gh_workflows_logs' to analize gh workflows logs");
+    let raw_logs = match run_ids {
+        Some(run_ids) => fetch_runs_logs(repository, run_ids, max_bytes).await?,
+        None => read_log(repository.workflows_log(), LogSlice::TailBytes(max_bytes)).await?,
+    };
+    // colours, timestamps and group markers only cost tokens
+    let logs = clean_log(&raw_logs);
+
+    // the analysis is still useful without the change context, so github errors are not fatal here
+    let mut context = if get_ci_provider().is_ok_and(|provider| provider.name() == "github") {
+        match failing_branches_context(repository).await {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Could not collect the commits of failing workflows: {}", e);
+                Vec::new()
             }
-            let workflows_ids = response.workflow_runs.iter().map(|wr| wr.id).collect::<Vec<u64>>();
-            info!("Downloaded logs for workflow run IDs: {:?}", workflows_ids);
+        }
+    } else {
+        Vec::new()
+    };
 
-            Ok(format!("Downloaded logs for workflow run IDs: {:?}", workflows_ids))
+    if let Some(checkout) = checkout {
+        let blamed = blame_context(checkout, &logs).await;
+        if !blamed.is_empty() {
+            context.push(format!("Source lines mentioned in the logs:\n{}", blamed.join("\n")));
         }
+    }
+
+    let context = (!context.is_empty()).then(|| context.join("\n"));
+    let respond = analyze_failure_logs(&logs, context.as_deref()).await?;
+
+    Ok(respond)
+}
+
+// the end of the logs of the runs, one after the other
+async fn fetch_runs_logs(repository: &Repository, run_ids: &[u64], max_bytes: u64) -> Result<String, Box<dyn Error>> {
+    let provider = get_ci_provider()?;
+    let project = repository.to_string();
+    let mut logs = String::new();
+    for run_id in run_ids {
+        let run_logs = provider.fetch_run_logs(&project, *run_id).await?;
+        logs.push_str(&format!("\n\nWORKFLOW {}\n{}", run_id, run_logs));
+    }
+
+    let mut start = logs.len().saturating_sub(max_bytes as usize);
+    while !logs.is_char_boundary(start) {
+        start += 1;
+    }
+    Ok(logs.split_off(start))
+}
+
+// change context for every workflow whose latest run on a branch failed
+async fn failing_branches_context(repository: &Repository) -> Result<Vec<String>, Box<dyn Error>> {
+    let (owner, repo) = (&repository.owner, &repository.name);
+    let client = shared_client()?;
+    let response = list_workflow_runs(&client, owner, repo).await?;
+
+    let mut seen = HashSet::new();
+    let mut context = Vec::new();
+    for run in &response.workflow_runs {
+        if !seen.insert((run.name.clone(), run.head_branch.clone())) || run.conclusion.as_deref() != Some("failure") {
+            continue;
+        }
+        if let Some(changes) = regression_context(&client, owner, repo, &response.workflow_runs, run).await? {
+            let workflow = run.name.as_deref().unwrap_or("workflow");
+            let branch = run.head_branch.as_deref().unwrap_or("unknown branch");
+            context.push(format!("Workflow {} on {}:\n{}", workflow, branch, changes));
+        }
+    }
+
+    Ok(context)
+}
+
+// commits and files changed since the workflow last passed on the same branch
+async fn regression_context(client: &GithubClient, owner: &str, repo: &str, runs: &[WorkflowRun], failed: &WorkflowRun) -> Result<Option<String>, GithubError> {
+    let Some(range) = find_regression_range(runs, failed) else {
+        return Ok(None);
+    };
+
+    let context = match range.last_green_sha {
+        Some(last_green) => {
+            let comparison = compare_commits(client, owner, repo, &last_green, &range.first_red_sha).await?;
+            format_change_context(&comparison.commits, &comparison.files)
+        }
+        // no green run in sight, so the best guess is the commit the failures started with
         None => {
-            error!("One of github environment variables is not found in environment variables");
-            Err("One of github environment variables is not found in environment variables".into())
+            let commit = get_commit(client, owner, repo, &range.first_red_sha).await?;
+            format_change_context(slice::from_ref(&commit), &commit.files)
+        }
+    };
+
+    Ok(Some(context))
+}
+
+// lines that are not part of the checkout, e.g. in dependencies, are skipped
+async fn blame_context(checkout: &Path, logs: &str) -> Vec<String> {
+    let mut blamed = Vec::new();
+    for (file, line) in source_locations(logs) {
+        if blamed.len() == MAX_BLAMED_LINES {
+            break;
+        }
+        if !checkout.join(&file).is_file() {
+            continue;
+        }
+        match blame_line(checkout, &file, line).await {
+            Ok(blame) => blamed.push(blame.to_string()),
+            Err(e) => warn!("Could not blame {}:{}: {}", file, line, e),
         }
     }
+    blamed
 }
 
-pub async fn list_workflows() -> Result<String, Box<dyn Error>> {
-    match get_github_env_data() {
-        Some(data) => {
-            info!("Using tool 'list_workflows' to get GitHub workflow runs");
+// only the error excerpts go to the llm, the whole log is sent when none of them is recognised,
+// and without an llm the excerpts are the analysis
+async fn analyze_failure_logs(logs: &str, context: Option<&str>) -> Result<String, Box<dyn Error>> {
+    let excerpts = extract_errors(logs);
+    let mut relevant = if excerpts.is_empty() {
+        logs.to_string()
+    } else {
+        format_excerpts(&excerpts)
+    };
+    let tests = parse_test_output(logs);
+    if tests.failed_cases().next().is_some() {
+        relevant = format!("Test results: {}\n\n{}", tests, relevant);
+    }
+
+    if !llm_configured() {
+        info!("No LLM configured, reporting {} error excerpts as they are", excerpts.len());
+        if excerpts.is_empty() {
+            return Ok(String::from("No errors recognised in the logs"));
+        }
+        return Ok(match context {
+            Some(context) => triage_prompt(&relevant, context),
+            None => relevant,
+        });
+    }
+
+    match context {
+        Some(context) => request_llm(&triage_prompt(&relevant, context), TRIAGE_SYSTEM_PROMPT).await,
+        None => request_llm(&relevant, SYSTEM_PROMPT).await,
+    }
+}
 
-            let (token, owner, repo) = (&data[0], &data[1], &data[2]);
-            let response = list_workflow_runs(owner, repo, &token).await?;
-                        
-            let mut output = String::new();
-            for run in &response.workflow_runs {
-                output.push_str(&format!("ID: {}, Status: {}, Conclusion: {:?}\n", run.id, run.status, run.conclusion));
+fn triage_prompt(logs: &str, context: &str) -> String {
+    format!("{}\n\nLogs:\n{}", context, logs)
+}
+
+// with set_status the failed commits are also marked with a "devops-agent/analysis" status
+pub async fn report_github_failures(repository: &Repository, set_status: bool) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'report_github_failures' to post failure analysis to pull requests");
+
+    let (owner, repo) = (&repository.owner, &repository.name);
+    let client = shared_client()?;
+    let response = list_workflow_runs(&client, owner, repo).await?;
+
+    // the pull request of a commit is looked up once, fork pull requests are not listed on the runs
+    let mut pr_by_sha = HashMap::new();
+    let mut pr_numbers = HashMap::new();
+    for run in response.workflow_runs.iter().filter(|run| run.status == "completed") {
+        let pr_number = match pr_by_sha.get(&run.head_sha) {
+            Some(pr_number) => *pr_number,
+            None => {
+                let pr_number = find_pull_request_number(&client, owner, repo, run).await?;
+                pr_by_sha.insert(run.head_sha.clone(), pr_number);
+                pr_number
+            }
+        };
+        match pr_number {
+            Some(pr_number) => {
+                pr_numbers.insert(run.id, pr_number);
+            }
+            None => info!("Workflow run ID: {} is not associated with a pull request", run.id),
+        }
+    }
+
+    let mut reported_runs = Vec::new();
+    for (run, pr_number) in runs_to_report(&response.workflow_runs, &pr_numbers) {
+        let existing = find_analysis_comment(&client, owner, repo, pr_number).await?;
+        if existing.as_ref().and_then(|comment| comment.body.as_deref()).is_some_and(|body| body.contains(&run_marker(run.id))) {
+            info!("Pull request #{} already has the analysis for workflow run ID: {}", pr_number, run.id);
+            continue;
+        }
+
+        let logs = fetch_workflow_logs(&client, owner, repo, run.id).await?;
+        let context = match regression_context(&client, owner, repo, &response.workflow_runs, run).await {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Could not collect the commits of workflow run ID: {}: {}", run.id, e);
+                None
             }
+        };
+        let analysis = analyze_failure_logs(&clean_log(&logs), context.as_deref()).await?;
+
+        let body = format_analysis_comment(run, &analysis);
+        upsert_pr_comment(&client, owner, repo, pr_number, existing.map(|comment| comment.id), &body).await?;
+        info!("Posted analysis of workflow run ID: {} to pull request #{}", run.id, pr_number);
 
-            info!("Retrieved {} workflow runs", response.workflow_runs.len());
-                        
-            Ok(output)
+        if set_status {
+            let description = format!("Workflow run {} failed, see the analysis in PR #{}", run.id, pr_number);
+            set_commit_status(&client, owner, repo, &run.head_sha, "failure", &description, &run.html_url).await?;
+            info!("Set commit status on {}", run.head_sha);
+        }
+
+        reported_runs.push(run.id);
+    }
+
+    let output = ToolOutput::from(format!("Reported analysis for workflow run IDs: {:?}", reported_runs));
+    Ok(output.with("run_ids", ArgValue::IntegerList(reported_runs)))
+}
+
+// an issue is opened once a failure occurred threshold times
+pub async fn track_recurring_failures(repository: &Repository, threshold: usize) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'track_recurring_failures' to open issues for recurring failures");
+
+    let (owner, repo) = (&repository.owner, &repository.name);
+    let client = shared_client()?;
+
+    let store_path = repository.data_dir.join(FAILURE_SIGNATURES_FILE);
+    let history_path = repository.data_dir.join(TEST_HISTORY_FILE);
+    let _locks = lock_files(&[&store_path, &history_path]).await;
+    let mut signatures = FailureSignatures::load(&store_path)?;
+    let mut history = TestHistory::load(&history_path)?;
+
+    let response = list_workflow_runs(&client, owner, repo).await?;
+    for run in response.workflow_runs.iter().filter(|run| run.conclusion.as_deref() == Some("failure")) {
+        // the runs classify_failures recorded are still checked here, each tool keeps its own record
+        if signatures.contains_run(run.id, run.run_attempt) {
+            continue;
+        }
+        let workflow = run.name.as_deref().unwrap_or("workflow");
+        let logs = fetch_workflow_logs(&client, owner, repo, run.id).await?;
+        signatures.mark_checked(run.id, run.run_attempt);
+
+        // transient and flaky failures are rerun rather than reported
+        let class = classify_failure(run.conclusion.as_deref(), &logs);
+        record_test_outcomes(&mut history, run.id, run.run_attempt, &run.head_sha, &logs);
+        if !class.needs_issue() || fails_only_flaky_tests(&history, &logs) {
+            info!("Workflow run ID: {} failed because of {} problems, not tracking it", run.id, class);
+            continue;
+        }
+
+        let signature = match failure_signature(workflow, &logs) {
+            Some(signature) => Some(signature),
+            None => step_signature(workflow, &failed_step_names(&list_run_jobs(&client, owner, repo, run.id).await?)),
+        };
+        let Some(signature) = signature else {
+            info!("Workflow run ID: {} failed without error lines or failed steps, not tracking it", run.id);
+            continue;
+        };
+        signatures.record(&signature, workflow, run.id, &run.html_url);
+        info!("Workflow run ID: {} failed with signature {}", run.id, signature);
+    }
+    signatures.save(&store_path)?;
+    history.save(&history_path)?;
+
+    let mut reported = Vec::new();
+    for signature in signatures.pending_reports(threshold) {
+        let record = signatures.signatures[&signature].clone();
+        let issue_number = report_recurring_failure(&client, owner, repo, &signature, &record).await?;
+
+        let record = signatures.signatures.get_mut(&signature).expect("signature was just listed");
+        record.reported_occurrences = record.occurrences.len();
+        record.issue_number = Some(issue_number);
+        // saving after every report, so a failed request does not duplicate the already posted ones
+        signatures.save(&store_path)?;
+
+        info!("Reported recurring failure {} in issue #{}", signature, issue_number);
+        reported.push(issue_number);
+    }
+
+    let output = ToolOutput::from(format!("Reported recurring failures in issues: {:?}", reported));
+    Ok(output.with("issue_numbers", ArgValue::IntegerList(reported)))
+}
+
+// classifies the failed runs and keeps the per-test history, transient failures and the ones caused only by flaky tests
+// are rerun once with auto_retry
+pub async fn classify_failures(repository: &Repository, filter: &RunFilter, auto_retry: bool) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'classify_failures' to classify failed runs and detect flaky tests");
+
+    let provider = get_ci_provider()?;
+    let project = repository.to_string();
+    let history_path = repository.data_dir.join(TEST_HISTORY_FILE);
+    let _locks = lock_files(&[&history_path]).await;
+    let mut history = TestHistory::load(&history_path)?;
+
+    let runs = filter.apply(provider.list_runs(&project).await?);
+    let mut output = Vec::new();
+    let (mut classified, mut retried) = (Vec::new(), Vec::new());
+    for run in runs {
+        if run.status != "completed" || history.contains_run(run.id, run.attempt) {
+            continue;
+        }
+        let logs = provider.fetch_run_logs(&project, run.id).await?;
+        record_test_outcomes(&mut history, run.id, run.attempt, &run.head_sha, &logs);
+
+        if matches!(run.conclusion.as_deref(), Some("success") | Some("skipped") | Some("neutral")) {
+            history.record_run(run.id, run.attempt, &run.head_sha, None);
+            continue;
+        }
+
+        let class = classify_failure(run.conclusion.as_deref(), &logs);
+        let flaky = fails_only_flaky_tests(&history, &logs);
+        // a failed retry is classified again but not retried a second time
+        let retry = auto_retry && run.attempt <= 1 && (class.is_transient() || flaky);
+        history.record_run(run.id, run.attempt, &run.head_sha, Some(class.to_string())).retried = retry;
+
+        let mut line = format!("Run {}: {}", run.id, class);
+        if flaky {
+            line.push_str(" (flaky tests only)");
+        }
+        if retry {
+            provider.retry_run(&project, run.id).await?;
+            line.push_str(", retried");
+            retried.push(run.id);
+        }
+        classified.push(run.id);
+        info!("{}", line);
+        output.push(line);
+    }
+    history.save(&history_path)?;
+
+    let flaky_tests = history.flaky_tests();
+    if !flaky_tests.is_empty() {
+        output.push(format!("Flaky tests: {}", flaky_tests.join(", ")));
+    }
+    let text = if output.is_empty() {
+        String::from("No new failed runs to classify")
+    } else {
+        output.join("\n")
+    };
+
+    Ok(ToolOutput::from(text)
+        .with("run_ids", ArgValue::IntegerList(classified))
+        .with("retried_run_ids", ArgValue::IntegerList(retried)))
+}
+
+// compares the latest failed run of every workflow and branch with the last run that passed there,
+// at most max_lines divergent lines are sent to the llm
+pub async fn diff_failed_logs(repository: &Repository, filter: &RunFilter, max_lines: usize) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'diff_failed_logs' to compare failed runs with the last passing ones");
+
+    let provider = get_ci_provider()?;
+    let project = repository.to_string();
+    let runs = provider.list_runs(&project).await?;
+
+    let mut sections = Vec::new();
+    for (failed, passing) in runs_to_compare(&runs, filter) {
+        let failing_logs = provider.fetch_run_logs(&project, failed.id).await?;
+        let passing_logs = provider.fetch_run_logs(&project, passing.id).await?;
+        let mut hunks = diff_logs(&passing_logs, &failing_logs);
+        let mut budget = max_lines;
+        hunks.retain_mut(|hunk| {
+            hunk.lines.truncate(budget);
+            budget -= hunk.lines.len();
+            !hunk.lines.is_empty()
+        });
+
+        let workflow = failed.name.as_deref().unwrap_or("workflow");
+        let branch = failed.branch.as_deref().unwrap_or("unknown branch");
+        sections.push(format!("Workflow {} on {}, run {} compared with run {}:\n{}", workflow, branch, failed.id, passing.id, format_hunks(&hunks)));
+    }
+
+    if sections.is_empty() {
+        return Ok("No failed runs with an earlier passing run to compare with".into());
+    }
+    let diff = sections.join("\n\n");
+    if !llm_configured() {
+        return Ok(diff);
+    }
+
+    request_llm(&diff, LOG_DIFF_SYSTEM_PROMPT).await
+}
+
+// the newest failed run of every workflow and branch picked by the filter, with the last run that passed before it.
+// The passing runs are looked for in every run, the filter usually leaves them out with run_ids
+pub fn runs_to_compare<'a>(runs: &'a [CiRun], filter: &RunFilter) -> Vec<(&'a CiRun, &'a CiRun)> {
+    // runs come newest first
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::new();
+    for failed in filter.apply(runs.to_vec()) {
+        if !seen.insert((failed.name.clone(), failed.branch.clone())) || failed.conclusion.as_deref() != Some("failure") {
+            continue;
+        }
+        let index = runs.iter().position(|run| run.id == failed.id).expect("filtered runs come from the listed ones");
+        let Some(passing) = runs[index + 1..]
+            .iter()
+            .find(|run| run.name == failed.name && run.branch == failed.branch && run.conclusion.as_deref() == Some("success")) else {
+            info!("No passing run to compare run ID: {} with", failed.id);
+            continue;
+        };
+        pairs.push((&runs[index], passing));
+    }
+    pairs
+}
+
+// records how long the successful runs, their jobs and steps took, and reports the new runs that were much slower
+// than the median of the runs of the previous window_days
+pub async fn track_durations(repository: &Repository, window_days: i64, threshold: f64) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'track_durations' to detect build duration regressions");
+
+    let window_secs = window_days.checked_mul(24 * 60 * 60).ok_or_else(|| format!("window_days {} is too large", window_days))?;
+
+    let (owner, repo) = (&repository.owner, &repository.name);
+    let client = shared_client()?;
+
+    let history_path = repository.data_dir.join(DURATION_HISTORY_FILE);
+    let _locks = lock_files(&[&history_path]).await;
+    let mut history = DurationHistory::load(&history_path)?;
+
+    let response = list_workflow_runs(&client, owner, repo).await?;
+    let mut recorded = HashSet::new();
+    // failed runs stop early, their durations would only pull the medians down
+    for run in response.workflow_runs.iter().filter(|run| run.conclusion.as_deref() == Some("success")) {
+        if history.contains_run(run.id) {
+            continue;
+        }
+        let Some(started_at) = run.run_started_at else {
+            continue;
+        };
+        let workflow = run.name.as_deref().unwrap_or("workflow");
+        let started_at = started_at.timestamp();
+        if let Some(secs) = duration_secs(run.run_started_at, run.updated_at) {
+            history.record(workflow, run.id, started_at, secs);
+        }
+
+        for job in list_run_jobs(&client, owner, repo, run.id).await? {
+            let job_key = format!("{} / {}", workflow, job.name);
+            if let Some(secs) = duration_secs(job.started_at, job.completed_at) {
+                history.record(&job_key, run.id, started_at, secs);
+            }
+            for step in job.steps.iter().filter(|step| step.conclusion.as_deref() == Some("success")) {
+                if let Some(secs) = duration_secs(step.started_at, step.completed_at) {
+                    history.record(&format!("{} / {}", job_key, step.name), run.id, started_at, secs);
+                }
+            }
+        }
+        history.record_run(run.id);
+        recorded.insert(run.id);
+    }
+    history.save(&history_path)?;
+
+    let regressions = history.regressions(&recorded, window_secs, threshold);
+    if regressions.is_empty() {
+        return Ok(format!("Recorded durations of {} runs, no regressions found", recorded.len()));
+    }
+    let lines = regressions.iter().map(|regression| regression.to_string()).collect::<Vec<String>>();
+    for line in &lines {
+        warn!("Duration regression: {}", line);
+    }
+
+    Ok(format!("Duration regressions over the last {} days:\n{}", window_days, lines.join("\n")))
+}
+
+fn record_test_outcomes(history: &mut TestHistory, run_id: u64, attempt: u64, sha: &str, logs: &str) {
+    for outcome in test_outcomes(logs) {
+        history.record_test(&outcome.name, run_id, attempt, sha, outcome.passed);
+    }
+}
+
+fn fails_only_flaky_tests(history: &TestHistory, logs: &str) -> bool {
+    let failed = test_outcomes(logs).into_iter().filter(|outcome| !outcome.passed).collect::<Vec<_>>();
+    !failed.is_empty() && failed.iter().all(|outcome| history.is_flaky(&outcome.name))
+}
+
+async fn report_recurring_failure(client: &GithubClient, owner: &str, repo: &str, signature: &str, record: &SignatureRecord) -> Result<u64, Box<dyn Error>> {
+    let label = signature_label(signature);
+    let new_occurrences = &record.occurrences[record.reported_occurrences..];
+    let links = new_occurrences
+        .iter()
+        .map(|occurrence| format!("- [run {}]({})", occurrence.run_id, occurrence.url))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let existing_issue = find_open_issue(client, owner, repo, &label).await?;
+    match existing_issue {
+        Some(issue) => {
+            let body = format!("Failure `{}` occurred again, {} times in total.\n\n{}\n", signature, record.occurrences.len(), links);
+            comment_on_issue(client, owner, repo, issue.number, &body).await?;
+            Ok(issue.number)
         }
         None => {
-            error!("One of github environment variables is not found in environment variables");
-            Err("One of github environment variables is not found in environment variables".into())
+            let latest = record.occurrences.last().expect("reported signatures have occurrences");
+            let logs = fetch_workflow_logs(client, owner, repo, latest.run_id).await?;
+            let analysis = analyze_failure_logs(&clean_log(&logs), None).await?;
+
+            let title = format!("Recurring failure in {} ({})", record.workflow, signature);
+            let body = format!(
+                "The same failure `{}` occurred in {} workflow runs.\n\n### Analysis\n\n{}\n\n### Runs\n\n{}\n",
+                signature,
+                record.occurrences.len(),
+                analysis,
+                links
+            );
+            let issue = create_issue(client, owner, repo, &title, &body, &label).await?;
+            Ok(issue.number)
         }
     }
 }
 
-pub async fn analize_agent_logs() -> Result<String, Box<dyn Error>> {
-    info!("Using tool 'analize_agent_logs' to analize agent log file");
+// bigger artifacts than max_artifact_size are build outputs rather than reports, so they are not downloaded,
+// nor unpacked beyond that size
+pub async fn analize_workflow_artifacts(repository: &Repository, max_artifact_size: u64) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'analize_workflow_artifacts' to inspect test and coverage reports of failed runs");
+
+    let (owner, repo) = (&repository.owner, &repository.name);
+    let client = shared_client()?;
+    let response = list_workflow_runs(&client, owner, repo).await?;
+
+    let mut summaries = Vec::new();
+    for run in response.workflow_runs.iter().filter(|run| run.conclusion.as_deref() == Some("failure")) {
+        let run_dir = repository.data_dir.join(ARTIFACTS_DIR).join(run.id.to_string());
+        // written once all artifacts of the run were looked at, a run that failed half way is tried again
+        let inspected_marker = run_dir.join(INSPECTED_MARKER);
+        if inspected_marker.exists() {
+            continue;
+        }
+
+        for artifact in list_run_artifacts(&client, owner, repo, run.id).await? {
+            if artifact.expired || artifact.size_in_bytes > max_artifact_size {
+                info!("Skipping artifact '{}' of workflow run ID: {}", artifact.name, run.id);
+                continue;
+            }
 
-    let file_name = PathBuf::from("logs/agent.log");
-    let prompt = read_file(file_name).await?;
+            let files = match download_artifact(&client, owner, repo, &artifact, &run_dir, max_artifact_size).await {
+                Ok(files) => files,
+                Err(e) if e.is::<ArtifactRefused>() => {
+                    warn!("Skipping artifact of workflow run ID: {}: {}", run.id, e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            info!("Downloaded {} files of artifact '{}' for workflow run ID: {}", files.len(), artifact.name, run.id);
 
+            for file in files {
+                if let Some(summary) = summarize_report(&file) {
+                    summaries.push(format!("Workflow run {}, artifact '{}', file {:?}:\n{}", run.id, artifact.name, file.file_name().unwrap_or_default(), summary));
+                }
+            }
+        }
+        create_dir_all(&run_dir)?;
+        File::create(&inspected_marker)?;
+    }
+
+    if summaries.is_empty() {
+        return Ok("No test or coverage reports found in the artifacts of failed workflow runs".into());
+    }
+
+    let prompt = summaries.join("\n\n");
     let respond = request_llm(&prompt, SYSTEM_PROMPT).await?;
 
     Ok(respond)
 }
 
-pub async fn analize_gh_workflows_logs() -> Result<String, Box<dyn Error>> {
-    info!("Using tool 'analize_gh_workflows_logs' to analize gh workflows logs");
-    let file_path = PathBuf::from("logs/gh_workflows.log");
-    let prompt = read_file(file_path).await?;
+// local checkout of the repository, the git tools and the blame context of the triage prompt need it
+pub fn git_checkout_dir() -> Option<PathBuf> {
+    var("GIT_CHECKOUT_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
 
-    let respond = request_llm(&prompt, SYSTEM_PROMPT).await?;
+pub async fn git_current_branch(checkout: &Path) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'git_current_branch' to get the branch of the local checkout");
 
-    Ok(respond)
+    let branch = current_branch(checkout).await?;
+    Ok(ToolOutput::from(branch.clone()).with("branch", ArgValue::Text(branch)))
+}
+
+pub async fn git_recent_commits(checkout: &Path, limit: usize) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'git_recent_commits' to list the latest commits of the local checkout");
+
+    let commits = recent_commits(checkout, limit).await?;
+
+    Ok(commits.iter().map(|commit| commit.to_string()).collect::<Vec<String>>().join("\n"))
+}
+
+pub async fn git_blame(checkout: &Path, file: &str, line: u32) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'git_blame' to find the commit that last changed a line");
+
+    let blame = blame_line(checkout, file, line).await?;
+
+    Ok(blame.to_string())
+}
+
+pub async fn git_diff(checkout: &Path, from: &str, to: &str) -> Result<String, Box<dyn Error>> {
+    info!("Using tool 'git_diff' to compare two refs of the local checkout");
+
+    diff_refs(checkout, from, to).await
+}
+
+// the argument vector of the command, e.g. ["cargo", "test", "--workspace"]
+pub async fn run_allowed_command(argv: &[String]) -> Result<ToolOutput, Box<dyn Error>> {
+    info!("Using tool 'run_command' to run {:?}", argv);
+
+    let output = run_command(&CommandPolicy::from_env(), argv).await?;
+    if !output.success() {
+        return Err(output.to_string().into());
+    }
+
+    Ok(ToolOutput::from(output.to_string()).with("stdout", ArgValue::Text(output.stdout)))
 }
 
 pub async fn clear_file(path: PathBuf) {
@@ -6,7 +6,7 @@ use dotenv::dotenv;
 use crate::utils::agent::start_agent;
 use crate::utils::{
     cli::{
-        start_cli, Mode, CLI
+        start_cli, Cli, Mode
     }, logging::init_logging
 };
 
@@ -16,7 +16,7 @@ mod utils;
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
     
-    match CLI::parse().mode {
+    match Cli::parse().mode {
         Mode::Agent => {
             init_logging();
             start_agent().await?;
@@ -1,28 +1,50 @@
 use std::{
-    env::var, error::Error, thread, time::Duration
+    collections::HashSet, env::var, error::Error, time::Duration
 };
 use agent_core::{
     agent_structs::{
-        AgentInput, DevOpsAgent, Step
+        AgentInput, DevOpsAgent, Repository
     }, run_agent
 };
+use futures::{
+    stream, StreamExt
+};
+use tool_executor::github_interaction::{
+    github_api_client::{
+        get_github_env_data, list_org_repositories
+    },
+    github_http_client::shared_client
+};
+use tokio::{
+    sync::mpsc, time::{sleep_until, Instant}
+};
 use tracing::{
-    error, info, warn
+    error, info, info_span, warn, Instrument
+};
+use crate::utils::{
+    get_env::{
+        get_max_parallel_repos, get_org, get_pipeline, get_repository_names, get_webhook_config
+    },
+    webhook::start_webhook_server
 };
-use crate::utils::get_env::get_pipeline;
 
 
 pub async fn start_agent() -> Result<(), Box<dyn Error>> {
 
     info!("Agent has been started");
-    
-    let steps: Vec<Step> = match get_pipeline() {
-        Some(pipeline) => pipeline,
-        None => {
-            error!("No PIPELINE environment variable found");
-            return Err("No PIPELINE environment variable found".into());
-        }
-    };
+
+    let mut agents = Vec::new();
+    for repository in get_repositories().await? {
+        let steps = match get_pipeline(&repository.to_string()).map_err(|e| format!("Invalid pipeline for {}: {}", repository, e))? {
+            Some(pipeline) => pipeline,
+            None => {
+                error!("No PIPELINE or PIPELINE_FILE environment variable found for {}", repository);
+                return Err(format!("No PIPELINE or PIPELINE_FILE environment variable found for {}", repository).into());
+            }
+        };
+        agents.push(DevOpsAgent::new(steps, repository));
+    }
+    info!("Monitoring {} repositories", agents.len());
 
     let timeout_hour = match var("TIMEOUT_HOUR") {
         Ok(val) => val.parse::<u64>().unwrap_or(2),
@@ -31,16 +53,83 @@ pub async fn start_agent() -> Result<(), Box<dyn Error>> {
             2
         },
     };
+    let max_parallel_repos = get_max_parallel_repos();
+
+    // without a webhook server the sender is dropped and the agent only polls
+    let (trigger_tx, mut trigger_rx) = mpsc::channel::<String>(32);
+    if let Some((addr, secret)) = get_webhook_config() {
+        start_webhook_server(&addr, secret, trigger_tx).await?;
+    }
 
-    let mut agent = DevOpsAgent::new(steps);
-            
+    let mut next_poll = Instant::now();
     loop {
-        let input = AgentInput {
-            message: String::from("Executing the planned steps."),
-            context: None,
-        };
-        run_agent(&mut agent, input).await;
+        tokio::select! {
+            _ = sleep_until(next_poll) => {
+                run_agents(agents.iter_mut().collect(), max_parallel_repos).await;
+                next_poll = Instant::now() + Duration::from_secs(timeout_hour * 3600);
+            }
+            Some(repository) = trigger_rx.recv() => {
+                // the workflows of a push complete one after the other, the triggers queued until then
+                // run the pipeline of each repository once
+                let mut repositories = HashSet::from([repository.to_lowercase()]);
+                while let Ok(repository) = trigger_rx.try_recv() {
+                    repositories.insert(repository.to_lowercase());
+                }
+                for repository in &repositories {
+                    if !agents.iter().any(|agent| agent.repository.to_string().to_lowercase() == *repository) {
+                        warn!("Received a webhook for {}, which is not monitored", repository);
+                    }
+                }
+                let triggered = agents
+                    .iter_mut()
+                    .filter(|agent| repositories.contains(&agent.repository.to_string().to_lowercase()))
+                    .collect::<Vec<&mut DevOpsAgent>>();
+                run_agents(triggered, max_parallel_repos).await;
+            }
+        }
+    }
+}
+
+async fn run_agents(agents: Vec<&mut DevOpsAgent>, max_parallel_repos: usize) {
+    stream::iter(agents)
+        .for_each_concurrent(max_parallel_repos, |agent| {
+            let span = info_span!("repository", name = %agent.repository);
+            async move {
+                let input = AgentInput {
+                    message: String::from("Executing the planned steps."),
+                    context: None,
+                };
+                run_agent(agent, input).await;
+            }.instrument(span)
+        })
+        .await;
+}
 
-        thread::sleep(Duration::from_secs(timeout_hour * 3600));
+// REPOS and ORG keep the data of every repository in its own directory,
+// a single OWNER/REPO keeps using the logs directory as is
+async fn get_repositories() -> Result<Vec<Repository>, Box<dyn Error>> {
+    if let Some(names) = get_repository_names() {
+        return names
+            .iter()
+            .map(|name| Repository::parse_namespaced(name).ok_or_else(|| format!("Invalid repository '{}' in REPOS, expected owner/repo", name).into()))
+            .collect();
+    }
+
+    if let Some(org) = get_org() {
+        info!("Discovering repositories of the {} organization", org);
+        let client = shared_client()?;
+        let repositories = list_org_repositories(&client, &org).await?;
+        return Ok(repositories
+            .iter()
+            .map(|repository| Repository::namespaced(&repository.owner.login, &repository.name))
+            .collect());
+    }
+
+    match get_github_env_data() {
+        Some(data) => Ok(vec![Repository::new(&data[0], &data[1])]),
+        None => {
+            error!("One of github environment variables is not found in environment variables");
+            Err("One of github environment variables is not found in environment variables".into())
+        }
     }
-}
\ No newline at end of file
+}
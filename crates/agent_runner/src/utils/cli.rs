@@ -1,19 +1,34 @@
 use std::{
-    error::Error, io::{self, Write}, path::PathBuf
+    error::Error, io::{self, Write}, path::{Path, PathBuf}
+};
+use agent_core::{
+    agent_structs::Repository, wrappers::{clear_file, AGENT_LOG_FILE}
 };
-use agent_core::wrappers::clear_file;
 use crossterm::{
     style::{Color, Stylize},
 };
 use clap::{
     Parser, ValueEnum
 };
-use tool_executor::process_execution::read_file;
+use tool_executor::{
+    github_interaction::github_api_client::get_github_env_data,
+    log_parser::{
+        annotations, AnnotationLevel
+    },
+    log_reader::{
+        read_log, LogSlice
+    }
+};
+
+use crate::utils::{
+    get_env::get_repository_names, wrappers::analize_logs
+};
 
-use crate::utils::wrappers::analize_logs;
+const DEFAULT_VIEW_LINES: usize = 200;
+const MAX_ANNOTATION_SCAN_BYTES: u64 = 16 * 1024 * 1024;
 
 #[derive(Parser)]
-pub struct CLI {
+pub struct Cli {
     #[clap(long, value_enum)]
     pub mode: Mode,
 }
@@ -27,6 +42,7 @@ pub enum Mode {
 pub async fn start_cli() -> Result<(), Box<dyn Error>> {
     println!("{}", DEVOPS_AGENT.with(Color::Rgb { r: 255, g: 70, b: 162 }).bold());
 
+    let mut repository = default_repository();
     let mut input = String::new();
     loop {
         print!("> ");
@@ -38,52 +54,18 @@ pub async fn start_cli() -> Result<(), Box<dyn Error>> {
         let command = input.trim();
 
         match command {
-            "-al" | "--agent-logs" => {
-                let file_name = PathBuf::from("logs/agent.log");
-
-                let content = match read_file(file_name).await {
-                        Ok(f) => f,
-                        Err(e) => {
-                            println!("{}: {}", "Error opening log file".with(Color::Red), e);
-                            continue;
-                        }
-                    };
-                
-                if content.len() > 0 {
-                    println!("{}", "Agent Logs".with(Color::Blue));
-                    println!("{}", content);
-                } else {
-                    println!("{}", "Agent logs are empty".with(Color::Blue));
-                }
-            },
-            "-wl" | "--workflow-logs" => {
-                let file_name = PathBuf::from("logs/gh_workflows.log");
-                
-                let content = match read_file(file_name).await {
-                        Ok(f) => f,
-                        Err(e) => {
-                            println!("{}: {}", "Error opening log file".with(Color::Red), e);
-                            continue;
-                        }
-                    };
-
-                if content.len() > 0 {
-                    println!("{}", "Workflow Logs".with(Color::Blue));
-                    println!("{}", content);
-                } else {
-                    println!("{}", "Workflow logs are empty".with(Color::Blue));
-                }
-            },
+            "-al" | "--agent-logs" => show_logs("Agent", &PathBuf::from(AGENT_LOG_FILE), DEFAULT_VIEW_LINES).await,
+            "-wl" | "--workflow-logs" => show_logs("Workflow", &repository.workflows_log(), DEFAULT_VIEW_LINES).await,
+            "-wa" | "--workflow-annotations" => show_annotations(&repository.workflows_log()).await,
             "-cal" | "--clear-agent-logs" => {
-                let file_path = PathBuf::from("logs/agent.log");
-                clear_file(file_path).await;
+                clear_file(PathBuf::from(AGENT_LOG_FILE)).await;
                 println!("{}", "Agent logs have been cleaned".with(Color::Blue));
             },
             "-cwl" | "--clear-workflow-logs" => {
-                let file_path = PathBuf::from("logs/gh_workflows.log");
-                clear_file(file_path).await;
+                clear_file(repository.workflows_log()).await;
                 println!("{}", "GitHub Workflow logs have been cleaned".with(Color::Blue));
             },
+            "-r" | "--repo" => show_repository(&repository),
             "-h" | "--help" => {
                 println!("{}", "Available Commands:".with(Color::Blue));
                 println!("{}", COMMANDS);
@@ -95,11 +77,31 @@ pub async fn start_cli() -> Result<(), Box<dyn Error>> {
             "" => continue,
             _ => {
                 let splitted_command = command.split_ascii_whitespace().collect::<Vec<&str>>();
-                let (first, second) = (splitted_command[0].trim(), splitted_command[1].trim());
-                if splitted_command.len() == 2 && (first == "-a" || first == "--analize") {
+                let [first, second] = splitted_command[..] else {
+                    println!("{}", "Invalid input".with(Color::Red));
+                    continue;
+                };
+                if let Ok(lines) = second.parse::<usize>() && matches!(first, "-al" | "--agent-logs" | "-wl" | "--workflow-logs") {
+                    match first {
+                        "-al" | "--agent-logs" => show_logs("Agent", &PathBuf::from(AGENT_LOG_FILE), lines).await,
+                        _ => show_logs("Workflow", &repository.workflows_log(), lines).await,
+                    }
+                    continue;
+                }
+                if first == "-r" || first == "--repo" {
+                    match Repository::parse_namespaced(second) {
+                        Some(selected) => {
+                            repository = selected;
+                            show_repository(&repository);
+                        }
+                        None => println!("{}", "Invalid repository, expected owner/repo".with(Color::Red)),
+                    }
+                    continue;
+                }
+                if first == "-a" || first == "--analize" {
                     let file_path = match second {
-                        "-al" => PathBuf::from("logs/agent.log"),
-                        "-wl" => PathBuf::from("logs/gh_workflows.log"),
+                        "-al" => PathBuf::from(AGENT_LOG_FILE),
+                        "-wl" => repository.workflows_log(),
                         _ => PathBuf::from(second),
                     };
                     let respond = match analize_logs(file_path).await {
@@ -121,6 +123,62 @@ pub async fn start_cli() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// the repository whose workflow logs are shown: the first of REPOS, or OWNER/REPO.
+// The repositories of an organization are picked with --repo
+fn default_repository() -> Repository {
+    get_repository_names()
+        .and_then(|names| names.first().and_then(|name| Repository::parse_namespaced(name)))
+        .or_else(|| get_github_env_data().map(|data| Repository::new(&data[0], &data[1])))
+        .unwrap_or_else(|| Repository::new("", ""))
+}
+
+fn show_repository(repository: &Repository) {
+    println!("{}", format!("Workflow logs are read from {}", repository.workflows_log().display()).with(Color::Blue));
+}
+
+// only the end of a log is printed, the rest would scroll out of the terminal anyway
+async fn show_logs(title: &str, file_name: &Path, lines: usize) {
+    let content = match read_log(file_name, LogSlice::TailLines(lines)).await {
+        Ok(content) => content,
+        Err(e) => {
+            println!("{}: {}", "Error opening log file".with(Color::Red), e);
+            return;
+        }
+    };
+
+    if !content.is_empty() {
+        println!("{}", format!("{} Logs", title).with(Color::Blue));
+        println!("{}", content);
+    } else {
+        println!("{}", format!("{} logs are empty", title).with(Color::Blue));
+    }
+}
+
+async fn show_annotations(file_name: &Path) {
+    let content = match read_log(file_name, LogSlice::TailBytes(MAX_ANNOTATION_SCAN_BYTES)).await {
+        Ok(content) => content,
+        Err(e) => {
+            println!("{}: {}", "Error opening log file".with(Color::Red), e);
+            return;
+        }
+    };
+
+    let annotations = annotations(&content);
+    if annotations.is_empty() {
+        println!("{}", "No errors or warnings annotated in the workflow logs".with(Color::Blue));
+        return;
+    }
+    println!("{}", "Workflow Annotations".with(Color::Blue));
+    for annotation in annotations {
+        let color = match annotation.level {
+            AnnotationLevel::Error => Color::Red,
+            AnnotationLevel::Warning => Color::Yellow,
+            AnnotationLevel::Notice => Color::Blue,
+        };
+        println!("{}", annotation.to_string().with(color));
+    }
+}
+
 const DEVOPS_AGENT: &str = r#"
 ________              ________                    _____                         __   
 \______ \   _______  _\_____  \ ______  ______   /  _  \    ____   ____   _____/  |_ 
@@ -133,10 +191,13 @@ ________              ________                    _____
 const COMMANDS: &str = r#"
     -a, --analize <file_name/-al/-wl>   Analyze logs (-al for agent logs, -wl for workflows)
 
-    -wl, --workflow-logs                View GitHub workflows logs
-    -al, --agent-logs                   View the agent logs
+    -wl, --workflow-logs [lines]        View the last lines of GitHub workflows logs, 200 by default
+    -wa, --workflow-annotations         View the errors and warnings annotated in GitHub workflows logs
+    -al, --agent-logs [lines]           View the last lines of the agent logs, 200 by default
     -cal, --clear-agent-logs            Clear agent logs
     -cwl, --clear-workflow-logs         Clear workflow logs
+    -r, --repo [owner/repo]             Show or select the repository whose workflow logs are used,
+                                        the first of REPOS (or OWNER/REPO) by default
     
     -h, --help                          See all available commands
     -q, --quit                          Quit the agent
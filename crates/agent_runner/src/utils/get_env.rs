@@ -1,16 +1,65 @@
-use std::env::var;
-use agent_core::agent_structs::Step;
+use std::{
+    env::var, error::Error, path::Path
+};
+use agent_core::{
+    agent_structs::Step, pipeline::load_pipeline_file, tool_args::parse_pipeline
+};
 
-pub fn get_pipeline() -> Option<Vec<Step>> {
-    if let Some(pipeline) = var("PIPELINE").ok() {
-        let pipeline = pipeline
-        .split_ascii_whitespace()
-        .map(|step_name| Step {
-            name: step_name.to_string(),
-            args: vec![],
-        })
-        .collect::<Vec<Step>>();
-        return Some(pipeline);
+// a repository can have its own pipeline, e.g. PIPELINE_FILE_LETV1NNN_DEVOPS_AGENT or PIPELINE_LETV1NNN_DEVOPS_AGENT
+// for letv1nnn/DevOps-Agent, otherwise the common PIPELINE_FILE or PIPELINE is used.
+// Unknown tools and invalid arguments fail here, before anything runs
+pub fn get_pipeline(repository: &str) -> Result<Option<Vec<Step>>, Box<dyn Error>> {
+    let suffix = repository_suffix(repository);
+    let candidates = [
+        (format!("PIPELINE_FILE_{}", suffix), true),
+        (format!("PIPELINE_{}", suffix), false),
+        (String::from("PIPELINE_FILE"), true),
+        (String::from("PIPELINE"), false),
+    ];
+
+    for (name, is_file) in candidates {
+        let Some(value) = var(&name).ok().filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        let steps = if is_file {
+            load_pipeline_file(Path::new(&value))?
+        } else {
+            parse_pipeline(&value)?
+        };
+        return Ok(Some(steps));
     }
-    None
-}
\ No newline at end of file
+    Ok(None)
+}
+
+fn repository_suffix(repository: &str) -> String {
+    repository
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect::<String>()
+}
+
+// whitespace-separated "owner/repo" list
+pub fn get_repository_names() -> Option<Vec<String>> {
+    var("REPOS")
+        .ok()
+        .map(|repos| repos.split_ascii_whitespace().map(String::from).collect::<Vec<String>>())
+        .filter(|repos| !repos.is_empty())
+}
+
+pub fn get_org() -> Option<String> {
+    var("ORG").ok().filter(|org| !org.is_empty())
+}
+
+pub fn get_max_parallel_repos() -> usize {
+    match var("MAX_PARALLEL_REPOS") {
+        Ok(val) => val.parse::<usize>().unwrap_or(4).max(1),
+        Err(_) => 4,
+    }
+}
+
+// webhooks are only accepted when a secret is configured to verify them
+pub fn get_webhook_config() -> Option<(String, String)> {
+    let secret = var("WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty())?;
+    let addr = var("WEBHOOK_ADDR").unwrap_or_else(|_| String::from("0.0.0.0:8080"));
+    Some((addr, secret))
+}
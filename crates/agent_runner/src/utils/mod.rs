@@ -2,4 +2,5 @@ pub mod logging;
 pub mod cli;
 pub mod get_env;
 pub mod agent;
-pub mod wrappers;
\ No newline at end of file
+pub mod wrappers;
+pub mod webhook;
\ No newline at end of file
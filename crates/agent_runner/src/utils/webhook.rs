@@ -0,0 +1,82 @@
+use std::{
+    error::Error, sync::Arc
+};
+use axum::{
+    body::Bytes, extract::State, http::{HeaderMap, StatusCode}, routing::post, Router
+};
+use tokio::{
+    net::TcpListener, sync::mpsc::{error::TrySendError, Sender}
+};
+use tool_executor::github_interaction::github_webhooks::{
+    triggered_repository, verify_signature
+};
+use tracing::{
+    error, info, warn
+};
+
+struct WebhookState {
+    secret: String,
+    // "owner/repo" of the repositories whose pipeline should run right away
+    triggers: Sender<String>,
+}
+
+// binding happens before returning, so a busy port fails the agent start instead of a background task
+pub async fn start_webhook_server(addr: &str, secret: String, triggers: Sender<String>) -> Result<(), Box<dyn Error>> {
+    let state = Arc::new(WebhookState {
+        secret,
+        triggers,
+    });
+    let router = Router::new()
+        .route("/webhook", post(handle_webhook))
+        .with_state(state);
+
+    let listener = TcpListener::bind(addr).await?;
+    info!("Listening for GitHub webhooks on {}", addr);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            error!("Webhook server stopped: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_webhook(State(state): State<Arc<WebhookState>>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let signature = headers
+        .get("x-hub-signature-256")
+        .and_then(|val| val.to_str().ok())
+        .unwrap_or_default();
+    if !verify_signature(state.secret.as_bytes(), &body, signature) {
+        warn!("Rejected a webhook with an invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let event = headers
+        .get("x-github-event")
+        .and_then(|val| val.to_str().ok())
+        .unwrap_or_default();
+
+    match triggered_repository(event, &body) {
+        Ok(Some(repository)) => {
+            info!("Received '{}' event for {}", event, repository);
+            // the agent does not read the triggers while it runs the pipelines, waiting here would time out the delivery
+            match state.triggers.try_send(repository) {
+                Ok(()) => StatusCode::ACCEPTED,
+                Err(TrySendError::Full(repository)) => {
+                    warn!("Too many pipeline runs are queued, dropping the webhook event for {}, the next poll covers it", repository);
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                Err(TrySendError::Closed(_)) => {
+                    error!("Agent loop is not running, dropping the webhook event");
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        }
+        Ok(None) => StatusCode::NO_CONTENT,
+        Err(e) => {
+            warn!("Invalid '{}' webhook payload: {}", event, e);
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use std::{error::Error, path::PathBuf};
 use crossterm::style::{Color, Stylize};
 use llm::request_llm;
-use tool_executor::process_execution::read_file;
+use agent_core::wrappers::MAX_PROMPT_LOG_BYTES;
+use tool_executor::log_reader::{
+    read_log, LogSlice
+};
 
 const SYSTEM_PROMPT: &str = "You are a helpful assistant that analizes and summarizes log files to human understandable format. You need to highlight any errors or warnings found in the logs. Should not be too long, so human could read them in just 1 minute, and structure your respond with bullet points";
 
 pub async fn analize_logs(file_path: PathBuf) -> Result<String, Box<dyn Error>> {
     let msg = format!("Analyzing the logs from {:?}", file_path);
     println!("{}", msg.with(Color::Blue));
-    let prompt = read_file(file_path).await?;
+    let prompt = read_log(file_path, LogSlice::TailBytes(MAX_PROMPT_LOG_BYTES)).await?;
     let respond = request_llm(&prompt, SYSTEM_PROMPT).await?;
     Ok(respond)
 }
\ No newline at end of file
@@ -34,6 +34,11 @@ trait RequestLlm {
 
 const ENV_ISSUE: &str = "Missing required environment variables: either OPENAI_API_KEY for OpenAI or MODEL for Ollama. Please set one of them in your .env file or system environment.";
 
+// without a model the agent falls back to the analysis it can do on its own
+pub fn llm_configured() -> bool {
+    var("MODEL").is_ok_and(|model| !model.is_empty())
+}
+
 pub async fn request_llm(prompt: &str, system_prompt: &str) -> Result<String, Box<dyn Error>> {
     let span = span!(Level::INFO, "");
 
@@ -41,8 +46,8 @@ pub async fn request_llm(prompt: &str, system_prompt: &str) -> Result<String, Bo
 
     let response: String;
 
-    if let Some(model) = var("MODEL").ok() {
-        if let Some(api_key) = var("OPENAI_API_KEY").ok() {
+    if let Ok(model) = var("MODEL") {
+        if let Ok(api_key) = var("OPENAI_API_KEY") {
             info!("All environment variables for OpenAI has been provided");
 
             let openai = Openai {
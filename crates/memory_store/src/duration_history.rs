@@ -0,0 +1,136 @@
+use serde::{
+    Deserialize, Serialize
+};
+use std::{
+    collections::{HashMap, HashSet}, error::Error, fmt::{self, Display, Formatter}, path::Path
+};
+use crate::persistence::{
+    load_json, save_json
+};
+
+// a few months of daily runs, the window the regressions are measured against is much shorter
+const MAX_SAMPLES_PER_KEY: usize = 200;
+// fewer earlier runs make a median that one slow runner can move
+const MIN_BASELINE_SAMPLES: usize = 5;
+
+// durations of successful runs, their jobs and steps, keyed by "workflow", "workflow / job" and "workflow / job / step"
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DurationHistory {
+    pub samples: HashMap<String, Vec<DurationSample>>,
+    pub runs: HashSet<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DurationSample {
+    pub run_id: u64,
+    // unix timestamp, in seconds
+    pub started_at: i64,
+    pub secs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurationRegression {
+    pub key: String,
+    pub run_id: u64,
+    pub secs: f64,
+    pub median_secs: f64,
+    pub increase_percent: f64,
+}
+
+impl Display for DurationRegression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} took {} in run {}, {:.0}% slower than its median of {}",
+            self.key, format_secs(self.secs), self.run_id, self.increase_percent, format_secs(self.median_secs)
+        )
+    }
+}
+
+impl DurationHistory {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        save_json(path, self)
+    }
+
+    pub fn contains_run(&self, run_id: u64) -> bool {
+        self.runs.contains(&run_id)
+    }
+
+    pub fn record_run(&mut self, run_id: u64) {
+        self.runs.insert(run_id);
+    }
+
+    // recording the same key of the same run twice is a no-op
+    pub fn record(&mut self, key: &str, run_id: u64, started_at: i64, secs: f64) {
+        let samples = self.samples.entry(key.to_string()).or_default();
+        if samples.iter().any(|sample| sample.run_id == run_id) {
+            return;
+        }
+        samples.push(DurationSample {
+            run_id,
+            started_at,
+            secs,
+        });
+        samples.sort_by_key(|sample| sample.started_at);
+        if samples.len() > MAX_SAMPLES_PER_KEY {
+            samples.remove(0);
+        }
+    }
+
+    // median of the samples started in the window before the given time
+    pub fn median_before(&self, key: &str, before: i64, window_secs: i64) -> Option<f64> {
+        let mut durations = self.samples
+            .get(key)?
+            .iter()
+            .filter(|sample| sample.started_at < before && sample.started_at >= before - window_secs)
+            .map(|sample| sample.secs)
+            .collect::<Vec<f64>>();
+        if durations.len() < MIN_BASELINE_SAMPLES {
+            return None;
+        }
+        durations.sort_by(f64::total_cmp);
+        let middle = durations.len() / 2;
+        Some(if durations.len() % 2 == 0 {
+            (durations[middle - 1] + durations[middle]) / 2.0
+        } else {
+            durations[middle]
+        })
+    }
+
+    // the given runs that took more than threshold_percent longer than the median of the runs in the window before them
+    pub fn regressions(&self, run_ids: &HashSet<u64>, window_secs: i64, threshold_percent: f64) -> Vec<DurationRegression> {
+        let mut regressions = Vec::new();
+        for (key, samples) in &self.samples {
+            for sample in samples.iter().filter(|sample| run_ids.contains(&sample.run_id)) {
+                let Some(median_secs) = self.median_before(key, sample.started_at, window_secs) else {
+                    continue;
+                };
+                let increase_percent = (sample.secs - median_secs) / median_secs * 100.0;
+                if median_secs > 0.0 && increase_percent > threshold_percent {
+                    regressions.push(DurationRegression {
+                        key: key.clone(),
+                        run_id: sample.run_id,
+                        secs: sample.secs,
+                        median_secs,
+                        increase_percent,
+                    });
+                }
+            }
+        }
+        regressions.sort_by(|a, b| a.key.cmp(&b.key).then(a.run_id.cmp(&b.run_id)));
+        regressions
+    }
+}
+
+fn format_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{
+    Deserialize, Serialize
+};
+use std::{
+    collections::HashMap, error::Error, path::Path
+};
+use crate::persistence::{
+    load_json, save_json
+};
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct FailureSignatures {
+    pub signatures: HashMap<String, SignatureRecord>,
+    // the attempt of every failed run that was looked at, also the ones that got no signature
+    #[serde(default)]
+    pub checked_runs: HashMap<u64, u64>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SignatureRecord {
+    pub workflow: String,
+    pub occurrences: Vec<FailureOccurrence>,
+    // how many occurrences were already reported, so the same runs are not reported twice
+    pub reported_occurrences: usize,
+    pub issue_number: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FailureOccurrence {
+    pub run_id: u64,
+    pub url: String,
+}
+
+impl FailureSignatures {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        save_json(path, self)
+    }
+
+    // a run that was retried since it was checked is checked again
+    pub fn contains_run(&self, run_id: u64, attempt: u64) -> bool {
+        self.checked_runs.get(&run_id) == Some(&attempt)
+    }
+
+    pub fn mark_checked(&mut self, run_id: u64, attempt: u64) {
+        self.checked_runs.insert(run_id, attempt);
+    }
+
+    // recording the same run twice is a no-op, also when it failed again in another attempt
+    pub fn record(&mut self, signature: &str, workflow: &str, run_id: u64, url: &str) -> &SignatureRecord {
+        let record = self.signatures.entry(signature.to_string()).or_default();
+        record.workflow = workflow.to_string();
+        if !record.occurrences.iter().any(|occurrence| occurrence.run_id == run_id) {
+            record.occurrences.push(FailureOccurrence {
+                run_id,
+                url: url.to_string(),
+            });
+        }
+        record
+    }
+
+    // signatures that reached the threshold and have occurrences which were not reported yet
+    pub fn pending_reports(&self, threshold: usize) -> Vec<String> {
+        let mut pending = self.signatures
+            .iter()
+            .filter(|(_, record)| record.occurrences.len() >= threshold && record.occurrences.len() > record.reported_occurrences)
+            .map(|(signature, _)| signature.clone())
+            .collect::<Vec<String>>();
+        pending.sort();
+        pending
+    }
+}
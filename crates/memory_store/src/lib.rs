@@ -1 +1,5 @@
-// memory store
\ No newline at end of file
+// memory store
+pub mod duration_history;
+pub mod failure_signatures;
+pub mod persistence;
+pub mod test_history;
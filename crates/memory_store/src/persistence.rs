@@ -0,0 +1,23 @@
+use serde::{
+    de::DeserializeOwned, Serialize
+};
+use std::{
+    error::Error, fs::{self, create_dir_all}, io::ErrorKind, path::Path
+};
+
+// missing file means nothing has been stored yet, so an empty structure is returned
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use serde::{
+    Deserialize, Serialize
+};
+use std::{
+    collections::{HashMap, HashSet}, error::Error, path::Path
+};
+use crate::persistence::{
+    load_json, save_json
+};
+
+// older results say little about the current state of a test
+const MAX_RESULTS_PER_TEST: usize = 50;
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct TestHistory {
+    pub tests: HashMap<String, Vec<TestResult>>,
+    pub runs: HashMap<u64, RunRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub run_id: u64,
+    #[serde(default = "first_attempt")]
+    pub attempt: u64,
+    pub sha: String,
+    pub passed: bool,
+}
+
+// a classified run, failed runs carry their classification. Only the latest attempt of a run is kept
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RunRecord {
+    #[serde(default = "first_attempt")]
+    pub attempt: u64,
+    pub sha: String,
+    pub classification: Option<String>,
+    pub retried: bool,
+}
+
+impl TestHistory {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        save_json(path, self)
+    }
+
+    // a run that was retried since it was recorded counts as a new one
+    pub fn contains_run(&self, run_id: u64, attempt: u64) -> bool {
+        self.runs.get(&run_id).is_some_and(|record| record.attempt == attempt)
+    }
+
+    pub fn record_run(&mut self, run_id: u64, attempt: u64, sha: &str, classification: Option<String>) -> &mut RunRecord {
+        let record = self.runs.entry(run_id).or_default();
+        record.attempt = attempt;
+        record.sha = sha.to_string();
+        record.classification = classification;
+        record.retried = false;
+        record
+    }
+
+    // recording the same test of the same attempt of a run twice is a no-op
+    pub fn record_test(&mut self, test: &str, run_id: u64, attempt: u64, sha: &str, passed: bool) {
+        let results = self.tests.entry(test.to_string()).or_default();
+        if results.iter().any(|result| result.run_id == run_id && result.attempt == attempt) {
+            return;
+        }
+        results.push(TestResult {
+            run_id,
+            attempt,
+            sha: sha.to_string(),
+            passed,
+        });
+        if results.len() > MAX_RESULTS_PER_TEST {
+            results.remove(0);
+        }
+    }
+
+    // a test that both passed and failed on the same commit does not depend on the code alone
+    pub fn is_flaky(&self, test: &str) -> bool {
+        let Some(results) = self.tests.get(test) else {
+            return false;
+        };
+        let passed = results.iter().filter(|result| result.passed).map(|result| &result.sha).collect::<HashSet<&String>>();
+        results.iter().any(|result| !result.passed && passed.contains(&result.sha))
+    }
+
+    pub fn flaky_tests(&self) -> Vec<String> {
+        let mut flaky = self.tests
+            .keys()
+            .filter(|test| self.is_flaky(test))
+            .cloned()
+            .collect::<Vec<String>>();
+        flaky.sort();
+        flaky
+    }
+}
+
+// the records written before attempts were kept are all of first attempts
+fn first_attempt() -> u64 {
+    1
+}
@@ -0,0 +1,166 @@
+// the system calls behind the command sandbox of tool_executor, Linux only.
+// The rest of the workspace forbids unsafe code, so it is kept to this crate and every unsafe block
+// explains why it is sound in a SAFETY comment, clippy rejects the ones without
+#![cfg(target_os = "linux")]
+
+use tokio::process::Command;
+use std::{
+    ffi::{CStr, CString}, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path, ptr::null
+};
+
+// what the forked child sets up for itself before the command is executed
+pub struct ChildSetup {
+    pub namespaces: Option<Namespaces>,
+    pub read_only: Option<ReadOnlyMount>,
+    pub cpu_seconds: Option<u64>,
+    pub memory_bytes: Option<u64>,
+}
+
+// an unprivileged user namespace is what allows to mount and to unshare the network without root
+pub struct Namespaces {
+    flags: libc::c_int,
+    uid_map: String,
+    gid_map: String,
+}
+
+pub struct ReadOnlyMount {
+    path: CString,
+    remount_flags: libc::c_ulong,
+}
+
+// must be called before the command is spawned, the setup runs in the child between fork and exec
+pub fn setup_child(command: &mut Command, setup: ChildSetup) {
+    // SAFETY: between fork and exec the child may only make async-signal-safe calls, another thread of the
+    // parent could hold the allocator or any other lock at the time of the fork. The closure only borrows
+    // what was built before the fork: the C strings, the id maps and the flags. It calls unshare, open, write,
+    // close, mount, chdir and setrlimit, all thin wrappers of system calls that neither allocate nor lock,
+    // and its errors come from io::Error::last_os_error, which reads errno without allocating
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(namespaces) = &setup.namespaces {
+                namespaces.enter()?;
+            }
+            if let Some(mount) = &setup.read_only {
+                mount.apply()?;
+            }
+            if let Some(seconds) = setup.cpu_seconds {
+                set_limit(libc::RLIMIT_CPU, seconds)?;
+            }
+            if let Some(bytes) = setup.memory_bytes {
+                set_limit(libc::RLIMIT_AS, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+// the group id is the pid of the command, a negative pid signals the whole group
+pub fn kill_process_group(pid: u32) {
+    // SAFETY: kill only takes integers and touches no memory of this process. At worst, once the whole group
+    // has exited and its id was reused, the signal reaches an unrelated group of the same user
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+impl Namespaces {
+    pub fn new(mount: bool, network: bool) -> Option<Self> {
+        if !mount && !network {
+            return None;
+        }
+        let mut flags = libc::CLONE_NEWUSER;
+        if mount {
+            flags |= libc::CLONE_NEWNS;
+        }
+        if network {
+            flags |= libc::CLONE_NEWNET;
+        }
+
+        // the command keeps running as the same user inside the namespace
+        // SAFETY: getuid and getgid cannot fail and touch no memory
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Some(Namespaces {
+            flags,
+            uid_map: format!("{0} {0} 1\n", uid),
+            gid_map: format!("{0} {0} 1\n", gid),
+        })
+    }
+
+    fn enter(&self) -> io::Result<()> {
+        // SAFETY: unshare only takes the flags and changes the namespaces of the calling process, the child
+        check(unsafe { libc::unshare(self.flags) })?;
+        write_proc_file(c"/proc/self/setgroups", b"deny")?;
+        write_proc_file(c"/proc/self/uid_map", self.uid_map.as_bytes())?;
+        write_proc_file(c"/proc/self/gid_map", self.gid_map.as_bytes())
+    }
+}
+
+impl ReadOnlyMount {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: the path is a NUL-terminated string and stat points to writable memory the size of a statvfs
+        check(unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) })?;
+        // SAFETY: statvfs succeeded, so it filled in the whole struct
+        let mount_flags = unsafe { stat.assume_init() }.f_flag;
+
+        // a remount inside a user namespace has to keep the flags the mount is locked with
+        let locked = libc::ST_NOSUID | libc::ST_NODEV | libc::ST_NOEXEC | libc::ST_NOATIME | libc::ST_NODIRATIME | libc::ST_RELATIME;
+        Ok(ReadOnlyMount {
+            path,
+            remount_flags: libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | (mount_flags & locked),
+        })
+    }
+
+    fn apply(&self) -> io::Result<()> {
+        // SAFETY: the paths are NUL-terminated strings owned by self, the null source, file system type and data
+        // are what mount expects for a propagation change, a bind mount and a remount. The mounts only change
+        // the mount namespace of the child, which it unshared before
+        unsafe {
+            // keeps the mounts below from propagating back to the host
+            check(libc::mount(null(), c"/".as_ptr(), null(), libc::MS_REC | libc::MS_PRIVATE, null()))?;
+            check(libc::mount(self.path.as_ptr(), self.path.as_ptr(), null(), libc::MS_BIND | libc::MS_REC, null()))?;
+            check(libc::mount(null(), self.path.as_ptr(), null(), self.remount_flags, null()))?;
+            // the working directory was entered before the fork, so it still points to the writable mount
+            check(libc::chdir(self.path.as_ptr()))
+        }
+    }
+}
+
+#[cfg(target_env = "gnu")]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(target_env = "gnu"))]
+type Resource = libc::c_int;
+
+fn set_limit(resource: Resource, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    // SAFETY: the limit is a valid rlimit that outlives the call
+    check(unsafe { libc::setrlimit(resource, &limit) })
+}
+
+fn write_proc_file(path: &CStr, content: &[u8]) -> io::Result<()> {
+    // SAFETY: the path is a NUL-terminated string, write reads content.len() bytes from the slice,
+    // and the file descriptor is only closed once, after the write
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY);
+        check(fd)?;
+        let written = libc::write(fd, content.as_ptr().cast(), content.len());
+        // read before close can overwrite errno
+        let error = io::Error::last_os_error();
+        libc::close(fd);
+        if written < 0 {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use reqwest::Method;
+use std::{
+    error::Error, sync::Arc
+};
+use crate::{
+    ci_providers::{
+        CiProvider, CiRun
+    },
+    github_interaction::{
+        github_api_client::{
+            fetch_workflow_logs, list_workflow_runs
+        },
+        github_http_client::{
+            shared_client, GithubClient
+        },
+        github_structs::WorkflowRun
+    }
+};
+
+pub struct GithubProvider {
+    client: Arc<GithubClient>,
+}
+
+impl GithubProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        Ok(GithubProvider {
+            client: shared_client()?,
+        })
+    }
+}
+
+impl From<&WorkflowRun> for CiRun {
+    fn from(run: &WorkflowRun) -> Self {
+        CiRun {
+            id: run.id,
+            attempt: run.run_attempt,
+            status: run.status.clone(),
+            conclusion: run.conclusion.clone(),
+            name: run.name.clone(),
+            branch: run.head_branch.clone(),
+            head_sha: run.head_sha.clone(),
+            url: run.html_url.clone(),
+        }
+    }
+}
+
+fn split_project(project: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    project
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid GitHub repository '{}', expected owner/repo", project).into())
+}
+
+#[async_trait]
+impl CiProvider for GithubProvider {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    async fn list_runs(&self, project: &str) -> Result<Vec<CiRun>, Box<dyn Error>> {
+        let (owner, repo) = split_project(project)?;
+        let response = list_workflow_runs(&self.client, owner, repo).await?;
+        Ok(response.workflow_runs.iter().map(CiRun::from).collect())
+    }
+
+    async fn fetch_run_logs(&self, project: &str, run_id: u64) -> Result<String, Box<dyn Error>> {
+        let (owner, repo) = split_project(project)?;
+        fetch_workflow_logs(&self.client, owner, repo, run_id).await
+    }
+
+    // only the failed jobs are run again, the successful ones keep their results
+    async fn retry_run(&self, project: &str, run_id: u64) -> Result<(), Box<dyn Error>> {
+        let (owner, repo) = split_project(project)?;
+        let url = self.client.url(&format!("/repos/{}/{}/actions/runs/{}/rerun-failed-jobs", owner, repo, run_id));
+        self.client.send(Method::POST, &url, &serde_json::Map::new()).await?;
+        Ok(())
+    }
+}
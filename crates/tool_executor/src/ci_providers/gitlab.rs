@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use reqwest::{
+    Client, RequestBuilder
+};
+use serde::{
+    de::DeserializeOwned, Deserialize
+};
+use std::{
+    env::var, error::Error
+};
+use crate::ci_providers::{
+    CiProvider, CiRun
+};
+
+const DEFAULT_GITLAB_URL: &str = "https://gitlab.com";
+// pipelines to list per project, the same amount github returns for workflow runs
+const PIPELINES_PER_PAGE: usize = 30;
+
+pub struct GitlabProvider {
+    client: Client,
+    // e.g. https://gitlab.com or https://gitlab.corp, without /api/v4
+    base_url: String,
+    token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Pipeline {
+    id: u64,
+    status: String,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    #[serde(default)]
+    sha: String,
+    #[serde(default)]
+    web_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Job {
+    id: u64,
+    name: String,
+    stage: String,
+    status: String,
+}
+
+impl GitlabProvider {
+    pub fn new(base_url: &str, token: &str) -> Self {
+        GitlabProvider {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let token = var("GITLAB_TOKEN").map_err(|_| "GITLAB_TOKEN is required when CI_PROVIDER is gitlab")?;
+        let base_url = var("GITLAB_URL").unwrap_or_else(|_| DEFAULT_GITLAB_URL.to_string());
+        Ok(Self::new(&base_url, &token))
+    }
+
+    // the api accepts the url-encoded project path in place of the numeric id
+    fn project_url(&self, project: &str, path: &str) -> String {
+        format!("{}/api/v4/projects/{}{}", self.base_url, project.replace('/', "%2F"), path)
+    }
+
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("User-Agent", "rust-agent")
+            .header("PRIVATE-TOKEN", &self.token)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Box<dyn Error>> {
+        let response = self.request(self.client.get(url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<T>()
+            .await?;
+        Ok(response)
+    }
+}
+
+// maps pipeline statuses to the github status and conclusion
+fn pipeline_state(status: &str) -> (String, Option<String>) {
+    let conclusion = match status {
+        "success" => "success",
+        "failed" => "failure",
+        "canceled" => "cancelled",
+        "skipped" => "skipped",
+        _ => return (status.to_string(), None),
+    };
+    (String::from("completed"), Some(conclusion.to_string()))
+}
+
+#[async_trait]
+impl CiProvider for GitlabProvider {
+    fn name(&self) -> &'static str {
+        "gitlab"
+    }
+
+    async fn list_runs(&self, project: &str) -> Result<Vec<CiRun>, Box<dyn Error>> {
+        let url = self.project_url(project, &format!("/pipelines?per_page={}", PIPELINES_PER_PAGE));
+        let pipelines = self.get_json::<Vec<Pipeline>>(&url).await?;
+
+        Ok(pipelines
+            .into_iter()
+            .map(|pipeline| {
+                let (status, conclusion) = pipeline_state(&pipeline.status);
+                CiRun {
+                    id: pipeline.id,
+                    // gitlab keeps no count of the retries of a pipeline
+                    attempt: 1,
+                    status,
+                    conclusion,
+                    name: Some(String::from("pipeline")),
+                    branch: pipeline.git_ref,
+                    head_sha: pipeline.sha,
+                    url: pipeline.web_url,
+                }
+            })
+            .collect())
+    }
+
+    // traces of every job in the pipeline, in the order gitlab lists them
+    async fn fetch_run_logs(&self, project: &str, run_id: u64) -> Result<String, Box<dyn Error>> {
+        let url = self.project_url(project, &format!("/pipelines/{}/jobs?per_page=100", run_id));
+        let jobs = self.get_json::<Vec<Job>>(&url).await?;
+
+        let mut logs = String::new();
+        for job in jobs {
+            let url = self.project_url(project, &format!("/jobs/{}/trace", job.id));
+            let trace = self.request(self.client.get(&url))
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            logs.push_str(&format!("\n== job {} (stage {}, {}) ==\n", job.name, job.stage, job.status));
+            logs.push_str(&trace);
+        }
+
+        Ok(logs)
+    }
+
+    // gitlab retries only the failed and cancelled jobs of the pipeline
+    async fn retry_run(&self, project: &str, run_id: u64) -> Result<(), Box<dyn Error>> {
+        let url = self.project_url(project, &format!("/pipelines/{}/retry", run_id));
+        self.request(self.client.post(&url))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use reqwest::{
+    Client, RequestBuilder, StatusCode
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    env::var, error::Error
+};
+use crate::ci_providers::{
+    CiProvider, CiRun
+};
+
+// builds to list per job, the same amount github returns for workflow runs
+const BUILDS_TREE: &str = "builds[number,result,building,url,actions[lastBuiltRevision[SHA1,branch[name]]]]{0,30}";
+// a running build keeps producing output, the rest is picked up by the next agent cycle
+const MAX_PROGRESSIVE_READS: usize = 5;
+
+pub struct JenkinsProvider {
+    client: Client,
+    base_url: String,
+    user: String,
+    api_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct JobInfo {
+    #[serde(default)]
+    builds: Vec<Build>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Build {
+    number: u64,
+    result: Option<String>,
+    #[serde(default)]
+    building: bool,
+    #[serde(default)]
+    url: String,
+    // most actions are empty objects, only the git one carries the revision
+    #[serde(default)]
+    actions: Vec<Option<BuildAction>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BuildAction {
+    #[serde(rename = "lastBuiltRevision")]
+    last_built_revision: Option<Revision>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Revision {
+    #[serde(rename = "SHA1")]
+    sha1: String,
+    #[serde(default)]
+    branch: Vec<RevisionBranch>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RevisionBranch {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BuildParameters {
+    #[serde(default)]
+    actions: Vec<Option<ParametersAction>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ParametersAction {
+    #[serde(default)]
+    parameters: Vec<BuildParameter>,
+}
+
+// jenkins leaves the value out for passwords and files
+#[derive(Deserialize, Debug)]
+struct BuildParameter {
+    name: String,
+    value: Option<Value>,
+}
+
+impl JenkinsProvider {
+    pub fn new(base_url: &str, user: &str, api_token: &str) -> Self {
+        JenkinsProvider {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user: user.to_string(),
+            api_token: api_token.to_string(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let base_url = var("JENKINS_URL").map_err(|_| "JENKINS_URL is required when CI_PROVIDER is jenkins")?;
+        let user = var("JENKINS_USER").map_err(|_| "JENKINS_USER is required when CI_PROVIDER is jenkins")?;
+        let api_token = var("JENKINS_TOKEN").map_err(|_| "JENKINS_TOKEN is required when CI_PROVIDER is jenkins")?;
+        Ok(Self::new(&base_url, &user, &api_token))
+    }
+
+    // "folder/job" is served under /job/folder/job/job
+    fn job_url(&self, project: &str, path: &str) -> String {
+        let job_path = project
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| format!("/job/{}", segment))
+            .collect::<String>();
+        format!("{}{}{}", self.base_url, job_path, path)
+    }
+
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("User-Agent", "rust-agent")
+            .basic_auth(&self.user, Some(&self.api_token))
+    }
+}
+
+// maps build results to the github status and conclusion
+fn build_state(build: &Build) -> (String, Option<String>) {
+    if build.building {
+        return (String::from("in_progress"), None);
+    }
+    let conclusion = match build.result.as_deref() {
+        Some("SUCCESS") => "success",
+        // unstable builds compiled, but their tests failed
+        Some("FAILURE") | Some("UNSTABLE") => "failure",
+        Some("ABORTED") => "cancelled",
+        Some("NOT_BUILT") => "skipped",
+        _ => return (String::from("queued"), None),
+    };
+    (String::from("completed"), Some(conclusion.to_string()))
+}
+
+#[async_trait]
+impl CiProvider for JenkinsProvider {
+    fn name(&self) -> &'static str {
+        "jenkins"
+    }
+
+    async fn list_runs(&self, project: &str) -> Result<Vec<CiRun>, Box<dyn Error>> {
+        let url = self.job_url(project, &format!("/api/json?tree={}", BUILDS_TREE));
+        let job = self.request(self.client.get(&url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JobInfo>()
+            .await?;
+
+        let job_name = project.rsplit('/').next().unwrap_or(project).to_string();
+        Ok(job.builds
+            .iter()
+            .map(|build| {
+                let (status, conclusion) = build_state(build);
+                let revision = build.actions
+                    .iter()
+                    .flatten()
+                    .find_map(|action| action.last_built_revision.as_ref());
+                CiRun {
+                    id: build.number,
+                    // a rebuild gets a new build number
+                    attempt: 1,
+                    status,
+                    conclusion,
+                    name: Some(job_name.clone()),
+                    branch: revision.and_then(|revision| revision.branch.first()).map(|branch| branch.name.clone()),
+                    head_sha: revision.map(|revision| revision.sha1.clone()).unwrap_or_default(),
+                    url: build.url.clone(),
+                }
+            })
+            .collect())
+    }
+
+    // the progressive api hands out the console in chunks, X-Text-Size is where the next one starts
+    // and X-More-Data tells that the build is still writing to it
+    async fn fetch_run_logs(&self, project: &str, run_id: u64) -> Result<String, Box<dyn Error>> {
+        let mut logs = String::new();
+        let mut start = 0u64;
+
+        for _ in 0..MAX_PROGRESSIVE_READS {
+            let url = self.job_url(project, &format!("/{}/logText/progressiveText?start={}", run_id, start));
+            let response = self.request(self.client.get(&url))
+                .send()
+                .await?
+                .error_for_status()?;
+
+            let headers = response.headers();
+            let next_start = headers
+                .get("x-text-size")
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| val.parse::<u64>().ok())
+                .unwrap_or(start);
+            let more_data = headers
+                .get("x-more-data")
+                .is_some_and(|val| val.as_bytes().eq_ignore_ascii_case(b"true"));
+
+            logs.push_str(&response.text().await?);
+
+            if !more_data || next_start <= start {
+                break;
+            }
+            start = next_start;
+        }
+
+        Ok(logs)
+    }
+
+    // jenkins cannot rerun a finished build as such: a parameterized build is queued again with its parameters,
+    // a pipeline build is replayed with its scripts. Any other build would pick up whatever is checked in now
+    async fn retry_run(&self, project: &str, run_id: u64) -> Result<(), Box<dyn Error>> {
+        let url = self.job_url(project, &format!("/{}/api/json?tree=actions[parameters[name,value]]", run_id));
+        let build = self.request(self.client.get(&url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BuildParameters>()
+            .await?;
+
+        let parameters = build.actions.into_iter().flatten().flat_map(|action| action.parameters).collect::<Vec<BuildParameter>>();
+        if !parameters.is_empty() {
+            let mut form = Vec::new();
+            for parameter in parameters {
+                let value = match parameter.value {
+                    Some(Value::String(value)) => value,
+                    Some(value) => value.to_string(),
+                    None => {
+                        return Err(format!(
+                            "Build {} of {} cannot be rerun, the value of its parameter '{}' is not exposed by Jenkins", run_id, project, parameter.name
+                        ).into());
+                    }
+                };
+                form.push((parameter.name, value));
+            }
+            let url = self.job_url(project, "/buildWithParameters");
+            self.request(self.client.post(&url).form(&form))
+                .send()
+                .await?
+                .error_for_status()?;
+            return Ok(());
+        }
+
+        let url = self.job_url(project, &format!("/{}/replay/rebuild", run_id));
+        let response = self.request(self.client.post(&url)).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(format!("Build {} of {} has no parameters to copy and cannot be replayed, only pipeline builds can be", run_id, project).into());
+        }
+        response.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use crossterm::style::Stylize;
+use tokio::{
+    fs::OpenOptions, io::AsyncWriteExt
+};
+use std::{
+    env::var, error::Error, fs::create_dir_all, path::Path
+};
+use crate::ci_providers::{
+    github::GithubProvider, gitlab::GitlabProvider, jenkins::JenkinsProvider
+};
+
+pub mod github;
+pub mod gitlab;
+pub mod jenkins;
+
+// a workflow run on github, a pipeline on gitlab, a build on jenkins
+#[derive(Debug, Clone, Default)]
+pub struct CiRun {
+    pub id: u64,
+    // counts up when a run is retried under the same id
+    pub attempt: u64,
+    pub status: String,
+    // github vocabulary is used for every provider: success, failure, cancelled, skipped, timed_out
+    pub conclusion: Option<String>,
+    pub name: Option<String>,
+    pub branch: Option<String>,
+    pub head_sha: String,
+    pub url: String,
+}
+
+// `project` is "owner/repo" on github, the full project path on gitlab and the job path (folder/job) on jenkins
+#[async_trait]
+pub trait CiProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn list_runs(&self, project: &str) -> Result<Vec<CiRun>, Box<dyn Error>>;
+    async fn fetch_run_logs(&self, project: &str, run_id: u64) -> Result<String, Box<dyn Error>>;
+    async fn retry_run(&self, project: &str, run_id: u64) -> Result<(), Box<dyn Error>>;
+}
+
+// CI_PROVIDER selects the backend, github is the default
+pub fn get_ci_provider() -> Result<Box<dyn CiProvider>, Box<dyn Error>> {
+    match var("CI_PROVIDER").unwrap_or_else(|_| String::from("github")).to_lowercase().as_str() {
+        "github" => Ok(Box::new(GithubProvider::from_env()?)),
+        "gitlab" => Ok(Box::new(GitlabProvider::from_env()?)),
+        "jenkins" => Ok(Box::new(JenkinsProvider::from_env()?)),
+        other => Err(format!("Unknown CI_PROVIDER '{}', expected github, gitlab or jenkins", other).into()),
+    }
+}
+
+pub async fn append_run_logs(log_file: &Path, run_id: u64, logs: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(log_dir) = log_file.parent() {
+        create_dir_all(log_dir)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(log_file)
+        .await?;
+
+    let header = format!("\n\n{} {}\n", "WORKFLOW".with(crossterm::style::Color::Red), run_id);
+    file.write_all(header.as_bytes()).await?;
+    file.write_all(logs.as_bytes()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::fmt::{
+    self, Display, Formatter
+};
+
+// lines kept around every error, the cause usually comes right before it and the details right after
+const CONTEXT_BEFORE: usize = 3;
+const CONTEXT_AFTER: usize = 6;
+// a log failing in hundreds of places is summed up well enough by its first errors
+const MAX_EXCERPTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Panic,
+    Compiler,
+    FailedTest,
+    Npm,
+    Python,
+    Maven,
+    ExitCode,
+    Error,
+}
+
+// an error line of the log together with the lines around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorExcerpt {
+    pub kind: ErrorKind,
+    // 1-based, of the first error line in the excerpt
+    pub line_number: usize,
+    pub headline: String,
+    pub lines: Vec<String>,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ErrorKind::Panic => "panic",
+            ErrorKind::Compiler => "compiler error",
+            ErrorKind::FailedTest => "failed test",
+            ErrorKind::Npm => "npm error",
+            ErrorKind::Python => "python error",
+            ErrorKind::Maven => "maven error",
+            ErrorKind::ExitCode => "exit code",
+            ErrorKind::Error => "error",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl Display for ErrorExcerpt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}: {}", self.kind, self.line_number, self.headline)?;
+        for line in &self.lines {
+            write!(f, "\n    {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// expects a log without colours and timestamps, see log_parser::clean_log
+pub fn extract_errors(log: &str) -> Vec<ErrorExcerpt> {
+    let lines = log.lines().collect::<Vec<&str>>();
+    let mut excerpts: Vec<ErrorExcerpt> = Vec::new();
+    let mut window_end = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        let Some(kind) = classify_line(line) else {
+            continue;
+        };
+
+        // an error inside the previous window only widens it, e.g. the "error: could not compile" after a diagnostic
+        if let Some(last) = excerpts.last_mut() && index < window_end {
+            let end = (index + CONTEXT_AFTER + 1).min(lines.len());
+            last.lines.extend(lines[window_end..end].iter().map(|line| line.to_string()));
+            window_end = end;
+            continue;
+        }
+        if excerpts.len() == MAX_EXCERPTS {
+            break;
+        }
+
+        let start = index.saturating_sub(CONTEXT_BEFORE).max(window_end);
+        let end = (index + CONTEXT_AFTER + 1).min(lines.len());
+        excerpts.push(ErrorExcerpt {
+            kind,
+            line_number: index + 1,
+            headline: line.trim().to_string(),
+            lines: lines[start..end].iter().map(|line| line.to_string()).collect(),
+        });
+        window_end = end;
+    }
+
+    excerpts
+}
+
+pub fn format_excerpts(excerpts: &[ErrorExcerpt]) -> String {
+    excerpts.iter().map(|excerpt| excerpt.to_string()).collect::<Vec<String>>().join("\n\n")
+}
+
+pub fn classify_line(line: &str) -> Option<ErrorKind> {
+    let line = line.trim();
+
+    if line.contains("panicked at") {
+        return Some(ErrorKind::Panic);
+    }
+    if line.starts_with("error[E") || line.starts_with("error: could not compile") || line.starts_with("error: aborting") {
+        return Some(ErrorKind::Compiler);
+    }
+    // cargo test, pytest, go test, jest
+    if (line.starts_with("test ") && line.ends_with("... FAILED"))
+        || line.starts_with("FAILED ")
+        || line.starts_with("--- FAIL:")
+        || line.starts_with("● ") {
+        return Some(ErrorKind::FailedTest);
+    }
+    if line.starts_with("npm ERR!") || line.starts_with("npm error") {
+        return Some(ErrorKind::Npm);
+    }
+    if line.starts_with("Traceback (most recent call last)") || line.starts_with("ERROR: Could not") || line.starts_with("ModuleNotFoundError") {
+        return Some(ErrorKind::Python);
+    }
+    if line.starts_with("[ERROR]") {
+        return Some(ErrorKind::Maven);
+    }
+    if let Some(code) = exit_code(line) && code != 0 {
+        return Some(ErrorKind::ExitCode);
+    }
+    // rustc, annotations rendered by clean_log, and most other tools
+    if line.starts_with("error:") || line.starts_with("error ") || line.starts_with("Error:") || line.starts_with("ERROR:") {
+        return Some(ErrorKind::Error);
+    }
+
+    None
+}
+
+// "Process completed with exit code 1." on github, "exited with code 1" or "exit status 1" elsewhere
+fn exit_code(line: &str) -> Option<i32> {
+    ["exit code ", "exited with code ", "exit status "]
+        .iter()
+        .find_map(|marker| line.split_once(marker))
+        .and_then(|(_, rest)| {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
+            digits.parse().ok()
+        })
+}
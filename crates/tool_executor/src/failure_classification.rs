@@ -0,0 +1,119 @@
+use std::fmt::{
+    self, Display, Formatter
+};
+use crate::{
+    error_extraction::{
+        classify_line, ErrorKind
+    },
+    log_parser::clean_log,
+    test_reports::{
+        parse_test_output, TestStatus
+    }
+};
+
+// log fragments telling why a run failed, matched case-insensitively
+const TIMEOUT_MARKERS: [&str; 3] = ["has exceeded the maximum execution time", "timed out after", "build timed out"];
+const INFRASTRUCTURE_MARKERS: [&str; 6] = [
+    "the runner has received a shutdown signal",
+    "lost communication with the server",
+    "the hosted runner encountered an error",
+    "no space left on device",
+    "the operation was canceled because the runner",
+    "cannot connect to the docker daemon",
+];
+const DEPENDENCY_MARKERS: [&str; 8] = [
+    "could not resolve host",
+    "failed to download",
+    "failed to fetch",
+    "npm err! network",
+    "could not find a version that satisfies the requirement",
+    "could not resolve dependencies",
+    "error: failed to get `",
+    "503 service unavailable",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    Infrastructure,
+    Dependency,
+    Compile,
+    Test,
+    Timeout,
+    Cancelled,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOutcome {
+    pub name: String,
+    pub passed: bool,
+}
+
+impl FailureClass {
+    // failures a rerun may fix, nothing in the code has to change for them
+    pub fn is_transient(&self) -> bool {
+        matches!(self, FailureClass::Infrastructure | FailureClass::Dependency | FailureClass::Timeout)
+    }
+
+    // failures somebody has to fix in the code, so recurring ones deserve an issue
+    pub fn needs_issue(&self) -> bool {
+        matches!(self, FailureClass::Compile | FailureClass::Test | FailureClass::Unknown)
+    }
+}
+
+impl Display for FailureClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let class = match self {
+            FailureClass::Infrastructure => "infrastructure",
+            FailureClass::Dependency => "dependency",
+            FailureClass::Compile => "compile",
+            FailureClass::Test => "test",
+            FailureClass::Timeout => "timeout",
+            FailureClass::Cancelled => "cancelled",
+            FailureClass::Unknown => "unknown",
+        };
+        write!(f, "{}", class)
+    }
+}
+
+// the environment breaking is checked first, a test failing after the runner lost its network is not the test's fault
+pub fn classify_failure(conclusion: Option<&str>, logs: &str) -> FailureClass {
+    let lowercase = logs.to_lowercase();
+    let mentions = |markers: &[&str]| markers.iter().any(|marker| lowercase.contains(marker));
+
+    if conclusion == Some("timed_out") || mentions(&TIMEOUT_MARKERS) {
+        return FailureClass::Timeout;
+    }
+    if conclusion == Some("cancelled") {
+        return FailureClass::Cancelled;
+    }
+    if mentions(&INFRASTRUCTURE_MARKERS) {
+        return FailureClass::Infrastructure;
+    }
+    if mentions(&DEPENDENCY_MARKERS) {
+        return FailureClass::Dependency;
+    }
+
+    let kinds = logs.lines().filter_map(classify_line).collect::<Vec<ErrorKind>>();
+    if kinds.contains(&ErrorKind::Compiler) {
+        return FailureClass::Compile;
+    }
+    if kinds.contains(&ErrorKind::FailedTest) || kinds.contains(&ErrorKind::Panic) || lowercase.contains("test result: failed") {
+        return FailureClass::Test;
+    }
+
+    FailureClass::Unknown
+}
+
+// per-test results of the test runners found in the logs
+pub fn test_outcomes(logs: &str) -> Vec<TestOutcome> {
+    parse_test_output(&clean_log(logs))
+        .cases
+        .into_iter()
+        .filter(|case| case.status != TestStatus::Skipped)
+        .map(|case| TestOutcome {
+            passed: case.status == TestStatus::Passed,
+            name: case.name,
+        })
+        .collect()
+}
@@ -1,80 +1,101 @@
-use crossterm::style::Stylize;
-use reqwest::Client;
-use tokio::{fs::OpenOptions, io::AsyncWriteExt};
 use zip::ZipArchive;
 use std::{
-    env::var, error::Error, fs::create_dir_all, io::Cursor, path::PathBuf
+    env::var, error::Error, fs::read, io::{Cursor, Read}, path::Path
 };
-use crate::github_interaction::github_structs::{
-    WorkflowRunsResponse
+use crate::{
+    ci_providers::append_run_logs,
+    github_interaction::{
+        github_app_auth::{
+            GithubAppAuth, GithubAuth
+        },
+        github_error::GithubError,
+        github_http_client::GithubClient,
+        github_structs::{
+            RepositoryInfo, WorkflowRunsResponse
+        }
+    }
 };
 
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
 pub fn get_github_env_data() -> Option<Vec<String>> {
-    if let Some(token) = var("GITHUB_TOKEN").ok() &&
-       let Some(owner) = var("OWNER").ok() &&
+    if let Some(owner) = var("OWNER").ok() &&
        let Some(repo) = var("REPO").ok() {
-        return Some(vec![token, owner, repo]);
+        return Some(vec![owner, repo]);
     }
     None
 }
 
-pub async fn list_workflow_runs(owner: &str, repo: &str, token: &str) -> Result<WorkflowRunsResponse, Box<dyn std::error::Error>> {
-    let url = format!("https://api.github.com/repos/{}/{}/actions/runs", owner, repo);
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .header("User-Agent", "rust-agent")
-        .bearer_auth(token)
-        .send()
-        .await?
-        .json::<WorkflowRunsResponse>()
-        .await?;
-
-    Ok(res)
+// a github app is preferred over the personal token when both are configured
+pub fn get_github_auth() -> Result<GithubAuth, Box<dyn Error>> {
+    if let Ok(app_id) = var("GITHUB_APP_ID") {
+        let key_path = var("GITHUB_APP_PRIVATE_KEY_PATH")
+            .map_err(|_| "GITHUB_APP_PRIVATE_KEY_PATH is required when GITHUB_APP_ID is set")?;
+        let installation_id = var("GITHUB_APP_INSTALLATION_ID")
+            .map_err(|_| "GITHUB_APP_INSTALLATION_ID is required when GITHUB_APP_ID is set")?
+            .parse::<u64>()?;
+        let private_key = read(key_path)?;
+
+        return Ok(GithubAuth::App(GithubAppAuth::new(&app_id, &private_key, installation_id)?));
+    }
+
+    match var("GITHUB_TOKEN") {
+        Ok(token) => Ok(GithubAuth::Token(token)),
+        Err(_) => Err("Either GITHUB_TOKEN or GITHUB_APP_ID has to be set to access GitHub".into()),
+    }
 }
 
-pub async fn download_workflow_logs(owner: &str, repo: &str, run_id: u64, token: &str) -> Result<(), Box<dyn std::error::Error>> {
-    create_dir_all("logs/workflows")?; // to store gh workflows
-    
-    let url = format!("https://api.github.com/repos/{}/{}/actions/runs/{}/logs", owner, repo, run_id);
-    let client = Client::new();
-    let bytes = client.get(&url)
-        .header("User-Agent", "rust-agent")
-        .bearer_auth(token)
-        .send()
-        .await?
-        .bytes()
-        .await?;
+// GitHub Enterprise Server serves the api under https://<host>/api/v3
+pub fn get_github_api_url() -> String {
+    var("GITHUB_API_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| DEFAULT_API_URL.to_string())
+}
 
-    let reader = Cursor::new(bytes);
-    let mut zip = ZipArchive::new(reader)?;
+pub async fn list_workflow_runs(client: &GithubClient, owner: &str, repo: &str) -> Result<WorkflowRunsResponse, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/actions/runs", owner, repo));
+    client.get_json(&url).await
+}
 
-    let mut workflows_logs = String::from(format!("\n\n{} {}\n", "WORKFLOW".with(crossterm::style::Color::Red), run_id));
+// repositories of an organization, archived ones are skipped since their workflows do not run anymore
+pub async fn list_org_repositories(client: &GithubClient, org: &str) -> Result<Vec<RepositoryInfo>, GithubError> {
+    let mut repositories = Vec::new();
 
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i)?;
-        println!("File: {}", file.name());
-        let mut contents = String::new();
-        use std::io::Read;
-        file.read_to_string(&mut contents)?;
-        // println!("{}", contents);
-        workflows_logs.push_str(&contents);
+    for page in 1.. {
+        let url = client.url(&format!("/orgs/{}/repos?per_page=100&page={}", org, page));
+        let batch = client.get_json::<Vec<RepositoryInfo>>(&url).await?;
+        let last_page = batch.len() < 100;
+        repositories.extend(batch.into_iter().filter(|repository| !repository.archived));
+        if last_page {
+            break;
+        }
     }
 
-    write_workflows(workflows_logs.as_bytes()).await?;
+    Ok(repositories)
+}
 
-    Ok(())
+pub async fn download_workflow_logs(client: &GithubClient, owner: &str, repo: &str, run_id: u64, log_file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = fetch_workflow_logs(client, owner, repo, run_id).await?;
+    append_run_logs(log_file, run_id, &contents).await
 }
 
-async fn write_workflows(workflow_logs: &[u8]) -> Result<(), Box<dyn Error>> {
-    let file_name = PathBuf::from("logs/gh_workflows.log");
-    let mut file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(file_name)
-        .await?;
+// returns the logs of every job in the run concatenated, without writing them anywhere
+pub async fn fetch_workflow_logs(client: &GithubClient, owner: &str, repo: &str, run_id: u64) -> Result<String, Box<dyn std::error::Error>> {
+    let url = client.url(&format!("/repos/{}/{}/actions/runs/{}/logs", owner, repo, run_id));
+    let bytes = client.get_bytes(&url).await?;
+
+    let reader = Cursor::new(bytes);
+    let mut zip = ZipArchive::new(reader)?;
+
+    let mut workflows_logs = String::new();
 
-    file.write_all(workflow_logs).await?;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        // a step printing invalid UTF-8 must not cost the logs of the whole run
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        workflows_logs.push_str(&String::from_utf8_lossy(&contents));
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(workflows_logs)
+}
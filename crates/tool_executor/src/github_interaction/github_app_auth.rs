@@ -0,0 +1,100 @@
+use chrono::{
+    DateTime, Duration, Utc
+};
+use jsonwebtoken::{
+    encode, Algorithm, EncodingKey, Header
+};
+use reqwest::{
+    header::{ACCEPT, USER_AGENT}, Client
+};
+use serde::{
+    Deserialize, Serialize
+};
+use tokio::sync::Mutex;
+use tracing::info;
+use crate::github_interaction::github_error::GithubError;
+
+// installation tokens live for an hour, they are refreshed a bit earlier so a request never carries an expired one
+const REFRESH_BEFORE_EXPIRY: Duration = Duration::minutes(5);
+
+pub enum GithubAuth {
+    Token(String),
+    App(GithubAppAuth),
+}
+
+// authenticates as a github app installation, so the agent acts as a bot with the permissions granted to the app
+pub struct GithubAppAuth {
+    app_id: String,
+    private_key: EncodingKey,
+    installation_id: u64,
+    installation_token: Mutex<Option<InstallationToken>>,
+}
+
+#[derive(Deserialize, Clone)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+impl GithubAppAuth {
+    pub fn new(app_id: &str, private_key_pem: &[u8], installation_id: u64) -> Result<Self, GithubError> {
+        let private_key = EncodingKey::from_rsa_pem(private_key_pem)
+            .map_err(|e| GithubError::AppAuth { message: format!("invalid app private key: {}", e) })?;
+
+        Ok(GithubAppAuth {
+            app_id: app_id.to_string(),
+            private_key,
+            installation_id,
+            installation_token: Mutex::new(None),
+        })
+    }
+
+    // the jwt identifies the app itself and is only good for exchanging it for an installation token
+    fn app_jwt(&self) -> Result<String, GithubError> {
+        let now = Utc::now();
+        let claims = AppClaims {
+            // backdated to tolerate clock drift between the agent and github
+            iat: (now - Duration::seconds(60)).timestamp(),
+            exp: (now + Duration::minutes(9)).timestamp(),
+            iss: self.app_id.clone(),
+        };
+
+        encode(&Header::new(Algorithm::RS256), &claims, &self.private_key)
+            .map_err(|e| GithubError::AppAuth { message: format!("failed to sign app jwt: {}", e) })
+    }
+
+    pub async fn installation_token(&self, client: &Client, api_url: &str) -> Result<String, GithubError> {
+        let mut cached = self.installation_token.lock().await;
+        if let Some(token) = cached.as_ref() && token.expires_at - REFRESH_BEFORE_EXPIRY > Utc::now() {
+            return Ok(token.token.clone());
+        }
+
+        info!("Requesting a new installation token for GitHub App {}", self.app_id);
+        let url = format!("{}/app/installations/{}/access_tokens", api_url, self.installation_id);
+        let response = client
+            .post(&url)
+            .header(USER_AGENT, "rust-agent")
+            .header(ACCEPT, "application/vnd.github+json")
+            .bearer_auth(self.app_jwt()?)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(GithubError::AppAuth {
+                message: format!("installation token request failed with {}", response.status()),
+            });
+        }
+
+        let token = response.json::<InstallationToken>().await?;
+        *cached = Some(token.clone());
+
+        Ok(token.token)
+    }
+}
@@ -0,0 +1,100 @@
+use zip::ZipArchive;
+use std::{
+    error::Error, fmt::{self, Display, Formatter}, fs::{create_dir_all, read_dir, remove_dir_all, File}, io::{copy, Cursor, Read}, path::{Path, PathBuf}
+};
+use crate::github_interaction::{
+    github_error::GithubError,
+    github_http_client::GithubClient,
+    github_structs::{
+        Artifact, ArtifactsResponse
+    }
+};
+
+pub async fn list_run_artifacts(client: &GithubClient, owner: &str, repo: &str, run_id: u64) -> Result<Vec<Artifact>, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/actions/runs/{}/artifacts?per_page=100", owner, repo, run_id));
+    let response = client.get_json::<ArtifactsResponse>(&url).await?;
+    Ok(response.artifacts)
+}
+
+// an artifact that is not unpacked because of its name or size, downloading it again would not change that
+#[derive(Debug)]
+pub struct ArtifactRefused(pub String);
+
+impl Display for ArtifactRefused {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArtifactRefused {}
+
+// unpacks the artifact into dest_dir/<artifact name> and returns the paths of all unpacked files,
+// at most max_unpacked_bytes are unpacked
+pub async fn download_artifact(
+    client: &GithubClient, owner: &str, repo: &str, artifact: &Artifact, dest_dir: &Path, max_unpacked_bytes: u64,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    // the artifact name comes from the workflow, so it is not trusted as a path
+    let dir_name = artifact.name.replace(['/', '\\'], "_");
+    if matches!(dir_name.as_str(), "" | "." | "..") {
+        return Err(ArtifactRefused(format!("Artifact name '{}' is not a valid directory name", artifact.name)).into());
+    }
+
+    let url = client.url(&format!("/repos/{}/{}/actions/artifacts/{}/zip", owner, repo, artifact.id));
+    let bytes = client.get_bytes(&url).await?;
+
+    let artifact_dir = dest_dir.join(dir_name);
+    // what is left of an earlier attempt that failed
+    if artifact_dir.exists() {
+        remove_dir_all(&artifact_dir)?;
+    }
+    create_dir_all(&artifact_dir)?;
+
+    if let Err(e) = unpack(&bytes, &artifact_dir, &artifact.name, max_unpacked_bytes) {
+        let _ = remove_dir_all(&artifact_dir);
+        return Err(e);
+    }
+
+    let mut files = Vec::new();
+    collect_files(&artifact_dir, &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+// the sizes written in the archive are not trusted, the unpacked bytes are counted instead
+fn unpack(bytes: &[u8], dir: &Path, name: &str, max_unpacked_bytes: u64) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+    let mut unpacked = 0;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let Some(relative) = entry.enclosed_name() else {
+            return Err(ArtifactRefused(format!("Artifact '{}' has the entry '{}' outside of its directory", name, entry.name())).into());
+        };
+        let path = dir.join(relative);
+        if entry.is_dir() {
+            create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = File::create(&path)?;
+        unpacked += copy(&mut (&mut entry).take(max_unpacked_bytes - unpacked + 1), &mut file)?;
+        if unpacked > max_unpacked_bytes {
+            return Err(ArtifactRefused(format!("Artifact '{}' unpacks to more than {} bytes", name, max_unpacked_bytes)).into());
+        }
+    }
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::github_interaction::{
+    github_error::GithubError,
+    github_http_client::GithubClient,
+    github_structs::{
+        ChangedFile, CommitInfo, CompareResponse, WorkflowRun
+    }
+};
+
+// keeps the triage prompt small on large merges
+const MAX_LISTED_COMMITS: usize = 20;
+const MAX_LISTED_FILES: usize = 50;
+
+// the commits between the last green and the first red run of a workflow on a branch
+#[derive(Debug, PartialEq)]
+pub struct RegressionRange {
+    pub last_green_sha: Option<String>,
+    pub first_red_sha: String,
+}
+
+pub async fn get_commit(client: &GithubClient, owner: &str, repo: &str, sha: &str) -> Result<CommitInfo, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/commits/{}", owner, repo, sha));
+    client.get_json(&url).await
+}
+
+pub async fn compare_commits(client: &GithubClient, owner: &str, repo: &str, base: &str, head: &str) -> Result<CompareResponse, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/compare/{}...{}", owner, repo, base, head));
+    client.get_json(&url).await
+}
+
+// runs are expected newest first, as github lists them; only runs of the same workflow and branch as `failed` are considered
+pub fn find_regression_range(runs: &[WorkflowRun], failed: &WorkflowRun) -> Option<RegressionRange> {
+    let mut first_red = None;
+
+    let same_history = runs
+        .iter()
+        .filter(|run| run.name == failed.name && run.head_branch == failed.head_branch)
+        .skip_while(|run| run.id != failed.id);
+
+    for run in same_history {
+        match run.conclusion.as_deref() {
+            Some("failure") | Some("timed_out") => first_red = Some(run),
+            Some("success") => {
+                return first_red.map(|red| RegressionRange {
+                    last_green_sha: Some(run.head_sha.clone()),
+                    first_red_sha: red.head_sha.clone(),
+                });
+            }
+            // cancelled, skipped or still running runs say nothing about the code
+            _ => {}
+        }
+    }
+
+    first_red.map(|red| RegressionRange {
+        last_green_sha: None,
+        first_red_sha: red.head_sha.clone(),
+    })
+}
+
+pub fn format_change_context(commits: &[CommitInfo], files: &[ChangedFile]) -> String {
+    let mut context = String::from("Commits since the last successful run:\n");
+    for commit in commits.iter().rev().take(MAX_LISTED_COMMITS) {
+        let author = commit.commit.author.as_ref().map(|author| author.name.as_str()).unwrap_or("unknown");
+        let title = commit.commit.message.lines().next().unwrap_or_default();
+        context.push_str(&format!("- {} by {}: {}\n", &commit.sha[..commit.sha.len().min(7)], author, title));
+    }
+    if commits.len() > MAX_LISTED_COMMITS {
+        context.push_str(&format!("- ... and {} older commits\n", commits.len() - MAX_LISTED_COMMITS));
+    }
+
+    context.push_str("\nChanged files:\n");
+    for file in files.iter().take(MAX_LISTED_FILES) {
+        context.push_str(&format!("- {} {} (+{} -{})\n", file.status, file.filename, file.additions, file.deletions));
+    }
+    if files.len() > MAX_LISTED_FILES {
+        context.push_str(&format!("- ... and {} more files\n", files.len() - MAX_LISTED_FILES));
+    }
+
+    context
+}
@@ -0,0 +1,53 @@
+use std::{
+    error::Error, fmt::{self, Display, Formatter}
+};
+
+#[derive(Debug)]
+pub enum GithubError {
+    Request(reqwest::Error),
+    Decode(serde_json::Error),
+    // reset_at is the unix time in seconds when the limit is lifted
+    RateLimited { reset_at: u64 },
+    Unauthorized { message: String },
+    AppAuth { message: String },
+    Forbidden { message: String },
+    NotFound { url: String },
+    Status { status: u16, message: String },
+}
+
+impl Display for GithubError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubError::Request(e) => write!(f, "GitHub request failed: {}", e),
+            GithubError::Decode(e) => write!(f, "Unexpected GitHub response: {}", e),
+            GithubError::RateLimited { reset_at } => write!(f, "GitHub rate limit exceeded, resets at unix time {}", reset_at),
+            GithubError::Unauthorized { message } => write!(f, "GitHub rejected the credentials: {}", message),
+            GithubError::AppAuth { message } => write!(f, "GitHub App authentication failed: {}", message),
+            GithubError::Forbidden { message } => write!(f, "GitHub denied access: {}", message),
+            GithubError::NotFound { url } => write!(f, "GitHub resource not found: {}", url),
+            GithubError::Status { status, message } => write!(f, "GitHub responded with {}: {}", status, message),
+        }
+    }
+}
+
+impl Error for GithubError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GithubError::Request(e) => Some(e),
+            GithubError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GithubError {
+    fn from(e: reqwest::Error) -> Self {
+        GithubError::Request(e)
+    }
+}
+
+impl From<serde_json::Error> for GithubError {
+    fn from(e: serde_json::Error) -> Self {
+        GithubError::Decode(e)
+    }
+}
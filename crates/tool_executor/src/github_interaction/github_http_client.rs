@@ -0,0 +1,267 @@
+use reqwest::{
+    header::{HeaderMap, ACCEPT, ETAG, IF_NONE_MATCH, LINK, RETRY_AFTER, USER_AGENT}, Client, Method, StatusCode
+};
+use serde::{
+    de::DeserializeOwned, Deserialize, Serialize
+};
+use bytes::Bytes;
+use tracing::{
+    info, warn
+};
+use std::{
+    collections::HashMap, error::Error, sync::{Arc, Mutex, OnceLock}, time::{Duration, SystemTime, UNIX_EPOCH}
+};
+use crate::github_interaction::{
+    github_api_client::{
+        get_github_api_url, get_github_auth
+    },
+    github_app_auth::{
+        GithubAppAuth, GithubAuth
+    },
+    github_error::GithubError
+};
+
+// waiting longer than that for the rate limit reset is left to the next agent cycle
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone)]
+struct CachedResponse {
+    etag: String,
+    body: Bytes,
+    next_page: Option<String>,
+}
+
+struct GithubResponse {
+    body: Bytes,
+    // url of the next page of a list, from the link header
+    next_page: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GithubErrorBody {
+    message: String,
+}
+
+pub struct GithubClient {
+    client: Client,
+    api_url: String,
+    auth: GithubAuth,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+    rate_limit_reset: Mutex<Option<u64>>,
+}
+
+// one client is shared by all tools, so the etag cache, rate limit state and installation token outlive a single agent cycle
+pub fn shared_client() -> Result<Arc<GithubClient>, Box<dyn Error>> {
+    static CLIENT: OnceLock<Arc<GithubClient>> = OnceLock::new();
+
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+    let client = Arc::new(GithubClient::with_auth(&get_github_api_url(), get_github_auth()?));
+
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+impl GithubClient {
+    pub fn new(api_url: &str, token: &str) -> Self {
+        Self::with_auth(api_url, GithubAuth::Token(token.to_string()))
+    }
+
+    pub fn with_app(api_url: &str, app: GithubAppAuth) -> Self {
+        Self::with_auth(api_url, GithubAuth::App(app))
+    }
+
+    pub fn with_auth(api_url: &str, auth: GithubAuth) -> Self {
+        GithubClient {
+            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            auth,
+            cache: Mutex::new(HashMap::new()),
+            rate_limit_reset: Mutex::new(None),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.api_url, path)
+    }
+
+    // GET requests for json are conditional, a 304 response is served from the cache and does not count against the rate limit
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, GithubError> {
+        let response = self.execute(Method::GET, url, None::<&()>, true).await?;
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    // one page of a list endpoint and the url of the next one, if any
+    pub async fn get_json_page<T: DeserializeOwned>(&self, url: &str) -> Result<(T, Option<String>), GithubError> {
+        let response = self.execute(Method::GET, url, None::<&()>, true).await?;
+        Ok((serde_json::from_slice(&response.body)?, response.next_page))
+    }
+
+    pub async fn get_bytes(&self, url: &str) -> Result<Bytes, GithubError> {
+        Ok(self.execute(Method::GET, url, None::<&()>, false).await?.body)
+    }
+
+    pub async fn send_json<B: Serialize, T: DeserializeOwned>(&self, method: Method, url: &str, body: &B) -> Result<T, GithubError> {
+        let response = self.execute(method, url, Some(body), false).await?;
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    // same as send_json, for endpoints whose response body is not needed
+    pub async fn send<B: Serialize>(&self, method: Method, url: &str, body: &B) -> Result<(), GithubError> {
+        self.execute(method, url, Some(body), false).await?;
+        Ok(())
+    }
+
+    async fn bearer_token(&self) -> Result<String, GithubError> {
+        match &self.auth {
+            GithubAuth::Token(token) => Ok(token.clone()),
+            GithubAuth::App(app) => app.installation_token(&self.client, &self.api_url).await,
+        }
+    }
+
+    async fn execute<B: Serialize>(&self, method: Method, url: &str, body: Option<&B>, use_cache: bool) -> Result<GithubResponse, GithubError> {
+        let mut waited = false;
+
+        loop {
+            self.wait_for_rate_limit().await?;
+            let token = self.bearer_token().await?;
+
+            let cached = if use_cache {
+                self.cache.lock().expect("github cache lock poisoned").get(url).cloned()
+            } else {
+                None
+            };
+
+            let mut request = self.client
+                .request(method.clone(), url)
+                .header(USER_AGENT, "rust-agent")
+                .header(ACCEPT, "application/vnd.github+json")
+                .bearer_auth(&token);
+            if let Some(cached) = &cached {
+                request = request.header(IF_NONE_MATCH, &cached.etag);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let response = request.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            self.update_rate_limit(&headers);
+
+            if status == StatusCode::NOT_MODIFIED && let Some(cached) = cached {
+                return Ok(GithubResponse {
+                    body: cached.body,
+                    next_page: cached.next_page,
+                });
+            }
+
+            if let Some(reset_at) = rate_limit_reset(status, &headers) {
+                let wait = Duration::from_secs(reset_at.saturating_sub(unix_now()));
+                if waited || wait > MAX_RATE_LIMIT_WAIT {
+                    return Err(GithubError::RateLimited { reset_at });
+                }
+                warn!("GitHub rate limit exceeded, waiting {} seconds before retrying", wait.as_secs());
+                tokio::time::sleep(wait).await;
+                waited = true;
+                continue;
+            }
+
+            if !status.is_success() {
+                let message = response
+                    .json::<GithubErrorBody>()
+                    .await
+                    .map(|body| body.message)
+                    .unwrap_or_default();
+                return Err(match status {
+                    StatusCode::UNAUTHORIZED => GithubError::Unauthorized { message },
+                    StatusCode::FORBIDDEN => GithubError::Forbidden { message },
+                    StatusCode::NOT_FOUND => GithubError::NotFound { url: url.to_string() },
+                    _ => GithubError::Status { status: status.as_u16(), message },
+                });
+            }
+
+            let etag = headers.get(ETAG).and_then(|val| val.to_str().ok()).map(String::from);
+            let next_page = next_page_url(&headers);
+            let bytes = response.bytes().await?;
+            if use_cache && let Some(etag) = etag {
+                self.cache.lock().expect("github cache lock poisoned").insert(url.to_string(), CachedResponse {
+                    etag,
+                    body: bytes.clone(),
+                    next_page: next_page.clone(),
+                });
+            }
+
+            return Ok(GithubResponse {
+                body: bytes,
+                next_page,
+            });
+        }
+    }
+
+    // a previous response may have told us that the limit is used up
+    async fn wait_for_rate_limit(&self) -> Result<(), GithubError> {
+        let reset_at = *self.rate_limit_reset.lock().expect("github rate limit lock poisoned");
+        let Some(reset_at) = reset_at else {
+            return Ok(());
+        };
+
+        let wait = Duration::from_secs(reset_at.saturating_sub(unix_now()));
+        if wait > MAX_RATE_LIMIT_WAIT {
+            return Err(GithubError::RateLimited { reset_at });
+        }
+        if !wait.is_zero() {
+            info!("GitHub rate limit is used up, waiting {} seconds for the reset", wait.as_secs());
+            tokio::time::sleep(wait).await;
+        }
+        *self.rate_limit_reset.lock().expect("github rate limit lock poisoned") = None;
+
+        Ok(())
+    }
+
+    fn update_rate_limit(&self, headers: &HeaderMap) {
+        let remaining = header_u64(headers, "x-ratelimit-remaining");
+        let reset = header_u64(headers, "x-ratelimit-reset");
+        if let (Some(0), Some(reset)) = (remaining, reset) {
+            *self.rate_limit_reset.lock().expect("github rate limit lock poisoned") = Some(reset);
+        }
+    }
+}
+
+// primary limits answer with 403/429 and zero remaining requests, secondary limits with a retry-after header
+fn rate_limit_reset(status: StatusCode, headers: &HeaderMap) -> Option<u64> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    if let Some(retry_after) = header_u64(headers, RETRY_AFTER.as_str()) {
+        return Some(unix_now() + retry_after);
+    }
+    match header_u64(headers, "x-ratelimit-remaining") {
+        Some(0) => Some(header_u64(headers, "x-ratelimit-reset").unwrap_or_else(|| unix_now() + 60)),
+        _ => None,
+    }
+}
+
+// the link header lists the pages as `<url>; rel="next", <url>; rel="last"`
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(LINK)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .find(|link| link.contains("rel=\"next\""))?
+        .split(';')
+        .next()
+        .map(|url| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
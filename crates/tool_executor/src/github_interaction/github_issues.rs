@@ -0,0 +1,89 @@
+use reqwest::Method;
+use sha2::{
+    Digest, Sha256
+};
+use crate::{
+    github_interaction::{
+        github_error::GithubError,
+        github_http_client::GithubClient,
+        github_structs::{
+            CommentRequest, CreateIssueRequest, Issue
+        }
+    },
+    log_parser::strip_timestamp
+};
+
+const SIGNATURE_LABEL_PREFIX: &str = "devops-agent:";
+// lines of the log that make up the signature
+const SIGNATURE_LINES: usize = 3;
+
+// hashes the first error lines of the log with timestamps and numbers stripped,
+// so the same failure in different runs gets the same signature. None when the log has no error lines
+pub fn failure_signature(workflow: &str, logs: &str) -> Option<String> {
+    let lines = logs.lines().map(strip_timestamp);
+
+    let mut error_lines = lines.clone()
+        .filter(|line| line.contains("##[error]"))
+        .take(SIGNATURE_LINES)
+        .collect::<Vec<&str>>();
+    if error_lines.is_empty() {
+        error_lines = lines
+            .filter(|line| line.to_lowercase().contains("error"))
+            .take(SIGNATURE_LINES)
+            .collect();
+    }
+
+    hash_signature(workflow, &error_lines)
+}
+
+// for failures without error text, signed by the failed jobs and steps, e.g. "build / Run tests",
+// so unexplained failures of different steps do not end up in one issue. None without failed steps
+pub fn step_signature(workflow: &str, failed_steps: &[String]) -> Option<String> {
+    hash_signature(workflow, &failed_steps.iter().map(String::as_str).collect::<Vec<&str>>())
+}
+
+fn hash_signature(workflow: &str, lines: &[&str]) -> Option<String> {
+    if lines.is_empty() {
+        return None;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(workflow.as_bytes());
+    for line in lines {
+        let normalized = line
+            .chars()
+            .map(|c| if c.is_ascii_digit() { '#' } else { c })
+            .collect::<String>();
+        hasher.update(normalized.trim().as_bytes());
+    }
+
+    Some(hasher.finalize()
+        .iter()
+        .take(6)
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+pub fn signature_label(signature: &str) -> String {
+    format!("{}{}", SIGNATURE_LABEL_PREFIX, signature)
+}
+
+pub async fn find_open_issue(client: &GithubClient, owner: &str, repo: &str, label: &str) -> Result<Option<Issue>, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/issues?state=open&labels={}", owner, repo, label));
+    let issues = client.get_json::<Vec<Issue>>(&url).await?;
+
+    Ok(issues.into_iter().next())
+}
+
+pub async fn create_issue(client: &GithubClient, owner: &str, repo: &str, title: &str, body: &str, label: &str) -> Result<Issue, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/issues", owner, repo));
+    client.send_json(Method::POST, &url, &CreateIssueRequest {
+        title,
+        body,
+        labels: vec![label],
+    }).await
+}
+
+pub async fn comment_on_issue(client: &GithubClient, owner: &str, repo: &str, issue_number: u64, body: &str) -> Result<(), GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/issues/{}/comments", owner, repo, issue_number));
+    client.send(Method::POST, &url, &CommentRequest { body }).await
+}
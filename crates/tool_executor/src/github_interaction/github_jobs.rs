@@ -0,0 +1,36 @@
+use chrono::{
+    DateTime, Utc
+};
+use crate::github_interaction::{
+    github_error::GithubError,
+    github_http_client::GithubClient,
+    github_structs::{
+        Job, JobsResponse
+    }
+};
+
+// only the latest attempt of a rerun run is listed
+pub async fn list_run_jobs(client: &GithubClient, owner: &str, repo: &str, run_id: u64) -> Result<Vec<Job>, GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/actions/runs/{}/jobs?per_page=100", owner, repo, run_id));
+    let response = client.get_json::<JobsResponse>(&url).await?;
+    Ok(response.jobs)
+}
+
+// None while the run, job or step has not finished, github also reports skipped steps with no duration this way
+pub fn duration_secs(started_at: Option<DateTime<Utc>>, completed_at: Option<DateTime<Utc>>) -> Option<f64> {
+    let elapsed = completed_at? - started_at?;
+    (elapsed.num_milliseconds() >= 0).then(|| elapsed.num_milliseconds() as f64 / 1000.0)
+}
+
+// "job / step" for every failed step, or the job alone when none of its steps is marked failed
+pub fn failed_step_names(jobs: &[Job]) -> Vec<String> {
+    let mut names = Vec::new();
+    for job in jobs.iter().filter(|job| job.conclusion.as_deref() == Some("failure")) {
+        let failed_steps = job.steps.iter().filter(|step| step.conclusion.as_deref() == Some("failure")).collect::<Vec<_>>();
+        if failed_steps.is_empty() {
+            names.push(job.name.clone());
+        }
+        names.extend(failed_steps.iter().map(|step| format!("{} / {}", job.name, step.name)));
+    }
+    names
+}
@@ -0,0 +1,113 @@
+use reqwest::Method;
+use std::collections::{
+    HashMap, HashSet
+};
+use crate::github_interaction::{
+    github_error::GithubError,
+    github_http_client::GithubClient,
+    github_structs::{
+        CommentRequest, CommitStatusRequest, IssueComment, PullRequestRef, WorkflowRun
+    }
+};
+
+// hidden marker, so the agent can find and update its own comment instead of posting a new one every cycle
+const ANALYSIS_MARKER: &str = "<!-- devops-agent:analysis";
+const STATUS_CONTEXT: &str = "devops-agent/analysis";
+// github rejects commit status descriptions longer than 140 characters
+const STATUS_DESCRIPTION_LIMIT: usize = 140;
+
+pub fn run_marker(run_id: u64) -> String {
+    format!("{} run_id={} -->", ANALYSIS_MARKER, run_id)
+}
+
+pub fn format_analysis_comment(run: &WorkflowRun, analysis: &str) -> String {
+    let name = run.name.as_deref().unwrap_or("workflow");
+    format!(
+        "{}\n### DevOps Agent: `{}` failed\n\nRun [{}]({}) on `{}`\n\n{}\n",
+        run_marker(run.id),
+        name,
+        run.id,
+        run.html_url,
+        run.head_sha,
+        analysis
+    )
+}
+
+pub async fn find_pull_request_number(client: &GithubClient, owner: &str, repo: &str, run: &WorkflowRun) -> Result<Option<u64>, GithubError> {
+    if let Some(pr) = run.pull_requests.first() {
+        return Ok(Some(pr.number));
+    }
+
+    // pull requests from forks are not listed on the run, so look them up by the head commit
+    let url = client.url(&format!("/repos/{}/{}/commits/{}/pulls", owner, repo, run.head_sha));
+    let pulls = client.get_json::<Vec<PullRequestRef>>(&url).await?;
+
+    Ok(pulls.first().map(|pr| pr.number))
+}
+
+// pull requests with long discussions spread their comments over several pages
+pub async fn find_analysis_comment(client: &GithubClient, owner: &str, repo: &str, pr_number: u64) -> Result<Option<IssueComment>, GithubError> {
+    let mut next_page = Some(client.url(&format!("/repos/{}/{}/issues/{}/comments?per_page=100", owner, repo, pr_number)));
+    while let Some(url) = next_page {
+        let (comments, next) = client.get_json_page::<Vec<IssueComment>>(&url).await?;
+        let found = comments
+            .into_iter()
+            .find(|comment| comment.body.as_deref().is_some_and(|body| body.starts_with(ANALYSIS_MARKER)));
+        if found.is_some() {
+            return Ok(found);
+        }
+        next_page = next;
+    }
+
+    Ok(None)
+}
+
+// the failed runs worth a comment, with their pull request: only the latest commit of a pull request counts,
+// and on it the newest run of each workflow, so a pull request whose failure was fixed or rerun green is left alone.
+// Runs come newest first, pr_numbers maps the run ids to their pull request
+pub fn runs_to_report<'a>(runs: &'a [WorkflowRun], pr_numbers: &HashMap<u64, u64>) -> Vec<(&'a WorkflowRun, u64)> {
+    let mut latest_shas = HashMap::new();
+    let mut seen_workflows = HashSet::new();
+    let mut reported_prs = HashSet::new();
+    let mut selected = Vec::new();
+
+    for run in runs.iter().filter(|run| run.status == "completed") {
+        let Some(pr_number) = pr_numbers.get(&run.id).copied() else {
+            continue;
+        };
+        if *latest_shas.entry(pr_number).or_insert(run.head_sha.as_str()) != run.head_sha {
+            continue;
+        }
+        if !seen_workflows.insert((pr_number, run.name.as_deref())) {
+            continue;
+        }
+        if run.conclusion.as_deref() == Some("failure") && reported_prs.insert(pr_number) {
+            selected.push((run, pr_number));
+        }
+    }
+    selected
+}
+
+// updates the existing comment when its id is given, otherwise posts a new one
+pub async fn upsert_pr_comment(client: &GithubClient, owner: &str, repo: &str, pr_number: u64, existing_comment: Option<u64>, body: &str) -> Result<u64, GithubError> {
+    let (method, url) = match existing_comment {
+        Some(comment_id) => (Method::PATCH, client.url(&format!("/repos/{}/{}/issues/comments/{}", owner, repo, comment_id))),
+        None => (Method::POST, client.url(&format!("/repos/{}/{}/issues/{}/comments", owner, repo, pr_number))),
+    };
+
+    let comment = client.send_json::<_, IssueComment>(method, &url, &CommentRequest { body }).await?;
+
+    Ok(comment.id)
+}
+
+pub async fn set_commit_status(client: &GithubClient, owner: &str, repo: &str, sha: &str, state: &str, description: &str, target_url: &str) -> Result<(), GithubError> {
+    let url = client.url(&format!("/repos/{}/{}/statuses/{}", owner, repo, sha));
+    let description = description.chars().take(STATUS_DESCRIPTION_LIMIT).collect::<String>();
+
+    client.send(Method::POST, &url, &CommitStatusRequest {
+        state,
+        target_url,
+        description: &description,
+        context: STATUS_CONTEXT,
+    }).await
+}
@@ -1,13 +1,165 @@
-use serde::Deserialize;
+use chrono::{
+    DateTime, Utc
+};
+use serde::{
+    Deserialize, Serialize
+};
 
 #[derive(Deserialize, Debug)]
 pub struct WorkflowRunsResponse {
     pub workflow_runs: Vec<WorkflowRun>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct WorkflowRun {
     pub id: u64,
     pub status: String,
     pub conclusion: Option<String>,
+    // rerunning failed jobs keeps the run id and counts up the attempt
+    #[serde(default = "first_attempt")]
+    pub run_attempt: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub head_sha: String,
+    #[serde(default)]
+    pub head_branch: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+    // only filled by github for pull requests opened from the same repository
+    #[serde(default)]
+    pub pull_requests: Vec<PullRequestRef>,
+    // a completed run is not updated anymore, so updated_at is when it finished
+    #[serde(default)]
+    pub run_started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+fn first_attempt() -> u64 {
+    1
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PullRequestRef {
+    pub number: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IssueComment {
+    pub id: u64,
+    pub body: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CommentRequest<'a> {
+    pub body: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct CommitStatusRequest<'a> {
+    pub state: &'a str,
+    pub target_url: &'a str,
+    pub description: &'a str,
+    pub context: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Issue {
+    pub number: u64,
+    pub html_url: String,
+}
+
+#[derive(Serialize)]
+pub struct CreateIssueRequest<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub labels: Vec<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RepositoryInfo {
+    pub name: String,
+    pub owner: RepositoryOwner,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RepositoryOwner {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Artifact {
+    pub id: u64,
+    pub name: String,
+    pub size_in_bytes: u64,
+    #[serde(default)]
+    pub expired: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub commit: CommitDetails,
+    #[serde(default)]
+    pub files: Vec<ChangedFile>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitDetails {
+    pub message: String,
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitAuthor {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CompareResponse {
+    pub commits: Vec<CommitInfo>,
+    #[serde(default)]
+    pub files: Vec<ChangedFile>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChangedFile {
+    pub filename: String,
+    pub status: String,
+    pub additions: u64,
+    pub deletions: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JobsResponse {
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Job {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub steps: Vec<JobStep>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct JobStep {
+    pub name: String,
+    pub number: u64,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,45 @@
+use hmac::{
+    Hmac, Mac
+};
+use serde::Deserialize;
+use sha2::Sha256;
+
+#[derive(Deserialize)]
+struct WebhookPayload {
+    action: Option<String>,
+    repository: Option<PayloadRepository>,
+}
+
+#[derive(Deserialize)]
+struct PayloadRepository {
+    full_name: String,
+}
+
+// checks the X-Hub-Signature-256 header ("sha256=<hex hmac of the body>") in constant time
+pub fn verify_signature(secret: &[u8], body: &[u8], signature_header: &str) -> bool {
+    let Some(signature) = signature_header.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+// returns "owner/repo" of the repository whose pipeline should run for the event, None for events the agent ignores
+pub fn triggered_repository(event: &str, body: &[u8]) -> Result<Option<String>, serde_json::Error> {
+    if event != "workflow_run" && event != "check_suite" {
+        return Ok(None);
+    }
+
+    let payload = serde_json::from_slice::<WebhookPayload>(body)?;
+    if payload.action.as_deref() != Some("completed") {
+        return Ok(None);
+    }
+
+    Ok(payload.repository.map(|repository| repository.full_name))
+}
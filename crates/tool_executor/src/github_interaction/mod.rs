@@ -1,2 +1,11 @@
 pub mod github_api_client;
-pub mod github_structs;
\ No newline at end of file
+pub mod github_app_auth;
+pub mod github_artifacts;
+pub mod github_commits;
+pub mod github_error;
+pub mod github_http_client;
+pub mod github_issues;
+pub mod github_jobs;
+pub mod github_reporting;
+pub mod github_structs;
+pub mod github_webhooks;
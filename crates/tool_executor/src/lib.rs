@@ -1,2 +1,11 @@
 pub mod process_execution;
-pub mod github_interaction;
\ No newline at end of file
+pub mod ci_providers;
+pub mod error_extraction;
+pub mod failure_classification;
+pub mod github_interaction;
+pub mod local_git;
+pub mod log_diff;
+pub mod log_parser;
+pub mod log_reader;
+pub mod report_files;
+pub mod test_reports;
@@ -0,0 +1,138 @@
+use tokio::process::Command;
+use std::{
+    collections::HashSet, error::Error, fmt::{self, Display, Formatter}, path::Path
+};
+
+// inspection of a local checkout, so failures can be traced to commits without any network access
+#[derive(Debug, PartialEq)]
+pub struct LocalCommit {
+    pub sha: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BlameLine {
+    pub file: String,
+    pub line: u32,
+    pub sha: String,
+    pub author: String,
+    pub summary: String,
+    pub content: String,
+}
+
+impl Display for LocalCommit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({}, {})", short_sha(&self.sha), self.subject, self.author, self.date)
+    }
+}
+
+impl Display for BlameLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} last changed in {} by {}: {}\n  {}", self.file, self.line, short_sha(&self.sha), self.author, self.summary, self.content.trim())
+    }
+}
+
+pub async fn current_branch(repo_dir: &Path) -> Result<String, Box<dyn Error>> {
+    let branch = git(repo_dir, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    let branch = branch.trim();
+    // a detached checkout has no branch, the commit is the next best thing
+    if branch == "HEAD" {
+        return Ok(git(repo_dir, &["rev-parse", "HEAD"]).await?.trim().to_string());
+    }
+    Ok(branch.to_string())
+}
+
+pub async fn recent_commits(repo_dir: &Path, limit: usize) -> Result<Vec<LocalCommit>, Box<dyn Error>> {
+    let limit = format!("-n{}", limit);
+    let log = git(repo_dir, &["log", &limit, "--format=%H%x1f%an%x1f%aI%x1f%s"]).await?;
+
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\u{1f}');
+            Some(LocalCommit {
+                sha: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+pub async fn blame_line(repo_dir: &Path, file: &str, line: u32) -> Result<BlameLine, Box<dyn Error>> {
+    let range = format!("-L{},{}", line, line);
+    let porcelain = git(repo_dir, &["blame", "--porcelain", &range, "--", file]).await?;
+
+    let mut lines = porcelain.lines();
+    let sha = lines
+        .next()
+        .and_then(|header| header.split_whitespace().next())
+        .ok_or_else(|| format!("git blame returned nothing for {}:{}", file, line))?
+        .to_string();
+    let (mut author, mut summary, mut content) = (String::new(), String::new(), String::new());
+    for line in lines {
+        if let Some(val) = line.strip_prefix("author ") {
+            author = val.to_string();
+        } else if let Some(val) = line.strip_prefix("summary ") {
+            summary = val.to_string();
+        } else if let Some(val) = line.strip_prefix('\t') {
+            content = val.to_string();
+        }
+    }
+
+    Ok(BlameLine {
+        file: file.to_string(),
+        line,
+        sha,
+        author,
+        summary,
+        content,
+    })
+}
+
+pub async fn diff_refs(repo_dir: &Path, from: &str, to: &str) -> Result<String, Box<dyn Error>> {
+    for reference in [from, to] {
+        // refs come from the pipeline, they must not be taken for options
+        if reference.starts_with('-') {
+            return Err(format!("Invalid git ref '{}'", reference).into());
+        }
+    }
+    git(repo_dir, &["diff", from, to]).await
+}
+
+// "path/to/file.rs:12" and "path/to/file.rs:12:5" references, as compilers and test runners print them
+pub fn source_locations(logs: &str) -> Vec<(String, u32)> {
+    let mut seen = HashSet::new();
+    logs
+        .split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | ',' | '"' | '\'' | '`'))
+        .filter_map(|token| {
+            let mut parts = token.trim_start_matches("./").split(':');
+            let file = parts.next()?;
+            let line = parts.next()?.parse::<u32>().ok()?;
+            let looks_like_path = file.contains('.') && !file.contains("//") && !file.ends_with('.');
+            (looks_like_path && line > 0).then(|| (file.to_string(), line))
+        })
+        .filter(|location| seen.insert(location.clone()))
+        .collect()
+}
+
+async fn git(repo_dir: &Path, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(args)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
@@ -0,0 +1,125 @@
+use similar::{
+    capture_diff_slices_deadline, Algorithm, DiffOp
+};
+use std::{
+    fmt::{self, Display, Formatter}, time::{Duration, Instant}
+};
+use crate::log_parser::clean_log;
+
+// huge logs are not worth an exact alignment, the diff falls back to a coarser one after that
+const DIFF_DEADLINE: Duration = Duration::from_secs(5);
+
+// consecutive lines of the failing log that have no counterpart in the passing one
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogHunk {
+    // 1-based line of the cleaned failing log
+    pub start_line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Display for LogHunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@@ line {} @@", self.start_line)?;
+        for line in &self.lines {
+            write!(f, "\n+ {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// aligns both logs line by line once the parts that differ between any two runs are masked,
+// so only what the failing run did differently is left
+pub fn diff_logs(passing: &str, failing: &str) -> Vec<LogHunk> {
+    let passing = clean_log(passing);
+    let failing = clean_log(failing);
+    let failing_lines = failing.lines().collect::<Vec<&str>>();
+    let old = passing.lines().map(normalize_line).collect::<Vec<String>>();
+    let new = failing_lines.iter().map(|line| normalize_line(line)).collect::<Vec<String>>();
+
+    let ops = capture_diff_slices_deadline(Algorithm::Patience, &old, &new, Some(Instant::now() + DIFF_DEADLINE));
+    ops.iter()
+        .filter_map(|op| match *op {
+            DiffOp::Insert { new_index, new_len, .. } | DiffOp::Replace { new_index, new_len, .. } => Some((new_index, new_len)),
+            _ => None,
+        })
+        .filter_map(|(start, len)| {
+            let lines = failing_lines[start..start + len]
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>();
+            (!lines.is_empty()).then_some(LogHunk { start_line: start + 1, lines })
+        })
+        .collect()
+}
+
+pub fn format_hunks(hunks: &[LogHunk]) -> String {
+    hunks.iter().map(|hunk| hunk.to_string()).collect::<Vec<String>>().join("\n")
+}
+
+// durations, clock times, hashes, uuids and long numbers (ids, ports, sizes) change from run to run,
+// small numbers like exit codes stay
+pub fn normalize_line(line: &str) -> String {
+    mask_clock_times(line)
+        .split_inclusive(is_separator)
+        .map(|token| {
+            let word = token.trim_end_matches(is_separator);
+            match mask(word) {
+                Some(masked) => format!("{}{}", masked, &token[word.len()..]),
+                None => token.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | ',' | '=' | ':' | '/' | '"' | '\'')
+}
+
+// "10:30:05", the separators would otherwise split it into small numbers that are kept
+fn mask_clock_times(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut masked = String::with_capacity(line.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let is_time = bytes.len() - index >= 8
+            && [0, 1, 3, 4, 6, 7].iter().all(|offset| bytes[index + offset].is_ascii_digit())
+            && bytes[index + 2] == b':'
+            && bytes[index + 5] == b':';
+        if is_time {
+            masked.push_str("<time>");
+            index += 8;
+            continue;
+        }
+        let char_len = line[index..].chars().next().map_or(1, char::len_utf8);
+        masked.push_str(&line[index..index + char_len]);
+        index += char_len;
+    }
+    masked
+}
+
+fn mask(word: &str) -> Option<&'static str> {
+    if word.is_empty() {
+        return None;
+    }
+    if is_duration(word) {
+        return Some("<duration>");
+    }
+    let hex = word.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    if hex && word.len() >= 7 && word.chars().any(|c| c.is_ascii_digit()) {
+        return Some("<id>");
+    }
+    if word.chars().filter(|c| c.is_ascii_digit()).count() >= 4 && word.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Some("<number>");
+    }
+    None
+}
+
+// "0.23s", "12ms", "1.5min", "3m", "250µs"
+fn is_duration(word: &str) -> bool {
+    let unit_start = word.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(word.len());
+    let (value, unit) = word.split_at(unit_start);
+    !value.is_empty()
+        && value.starts_with(|c: char| c.is_ascii_digit())
+        && matches!(unit, "s" | "ms" | "us" | "µs" | "ns" | "m" | "min" | "h" | "sec" | "secs" | "seconds")
+}
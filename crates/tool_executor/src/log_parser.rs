@@ -0,0 +1,250 @@
+use std::fmt::{
+    self, Display, Formatter
+};
+
+// header written by append_run_logs in front of the logs of every run
+const RUN_HEADER: &str = "WORKFLOW ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnotationLevel {
+    Error,
+    Warning,
+    Notice,
+}
+
+// "##[error]..." lines and "::warning file=...::" workflow commands
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub level: AnnotationLevel,
+    pub message: String,
+    pub title: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEvent {
+    RunStart { run_id: u64 },
+    GroupStart { name: String },
+    GroupEnd,
+    Annotation(Annotation),
+    Line(String),
+}
+
+// the lines of one ##[group], or of the part of a run outside of any group
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LogGroup {
+    pub run_id: Option<u64>,
+    pub name: Option<String>,
+    pub lines: Vec<String>,
+    pub annotations: Vec<Annotation>,
+}
+
+impl Display for AnnotationLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationLevel::Error => write!(f, "error"),
+            AnnotationLevel::Warning => write!(f, "warning"),
+            AnnotationLevel::Notice => write!(f, "notice"),
+        }
+    }
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.level)?;
+        if let Some(file) = &self.file {
+            write!(f, " {}", file)?;
+            if let Some(line) = self.line {
+                write!(f, ":{}", line)?;
+            }
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        if let Some(title) = &self.title {
+            write!(f, " [{}]", title)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+pub fn parse_log(text: &str) -> Vec<LogEvent> {
+    text.lines().map(parse_line).collect()
+}
+
+pub fn parse_line(line: &str) -> LogEvent {
+    let line = strip_ansi(line);
+    let line = strip_timestamp(&line);
+
+    if let Some(run_id) = line.strip_prefix(RUN_HEADER).and_then(|id| id.trim().parse::<u64>().ok()) {
+        return LogEvent::RunStart { run_id };
+    }
+    if let Some(rest) = line.strip_prefix("##[")
+        && let Some((command, message)) = rest.split_once(']')
+        && let Some(event) = command_event(command, "", message) {
+        return event;
+    }
+    if let Some(rest) = line.strip_prefix("::") && let Some((command, message)) = rest.split_once("::") {
+        let (command, properties) = command.split_once(' ').unwrap_or((command, ""));
+        if let Some(event) = command_event(command, properties, &unescape(message)) {
+            return event;
+        }
+    }
+
+    LogEvent::Line(line.to_string())
+}
+
+// groups never nest in workflow logs, a new group or run closes the current one
+pub fn parse_groups(text: &str) -> Vec<LogGroup> {
+    let mut groups = Vec::new();
+    let mut current = LogGroup::default();
+    let mut run_id = None;
+
+    for event in parse_log(text) {
+        match event {
+            LogEvent::RunStart { run_id: id } => {
+                run_id = Some(id);
+                close(&mut current, &mut groups, run_id);
+            }
+            LogEvent::GroupStart { name } => {
+                close(&mut current, &mut groups, run_id);
+                current.name = Some(name);
+            }
+            LogEvent::GroupEnd => close(&mut current, &mut groups, run_id),
+            LogEvent::Annotation(annotation) => current.annotations.push(annotation),
+            LogEvent::Line(line) => current.lines.push(line),
+        }
+    }
+    close(&mut current, &mut groups, run_id);
+
+    groups
+}
+
+fn close(current: &mut LogGroup, groups: &mut Vec<LogGroup>, run_id: Option<u64>) {
+    let group = std::mem::replace(current, LogGroup { run_id, ..Default::default() });
+    // the blank lines between runs do not make a group
+    let has_lines = group.lines.iter().any(|line| !line.trim().is_empty());
+    if has_lines || !group.annotations.is_empty() || group.name.is_some() {
+        groups.push(group);
+    }
+}
+
+pub fn annotations(text: &str) -> Vec<Annotation> {
+    parse_log(text)
+        .into_iter()
+        .filter_map(|event| match event {
+            LogEvent::Annotation(annotation) => Some(annotation),
+            _ => None,
+        })
+        .collect()
+}
+
+// the log without colours, timestamps and group markers, annotations are kept as "level: message" lines
+pub fn clean_log(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for event in parse_log(text) {
+        match event {
+            LogEvent::RunStart { run_id } => cleaned.push_str(&format!("{}{}", RUN_HEADER, run_id)),
+            LogEvent::GroupStart { name } => cleaned.push_str(&name),
+            LogEvent::GroupEnd => continue,
+            LogEvent::Annotation(annotation) => cleaned.push_str(&annotation.to_string()),
+            LogEvent::Line(line) => cleaned.push_str(&line),
+        }
+        cleaned.push('\n');
+    }
+    cleaned
+}
+
+// removes CSI sequences (colours, cursor movement) and OSC sequences (titles, hyperlinks)
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                // parameters and intermediates, up to the final byte in @..~
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                // terminated by BEL or by ESC \
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stripped
+}
+
+// github prefixes every line with an RFC 3339 timestamp, e.g. "2024-05-01T10:00:00.1234567Z "
+pub fn strip_timestamp(line: &str) -> &str {
+    let Some((timestamp, rest)) = line.split_once(' ') else {
+        return line;
+    };
+    let bytes = timestamp.as_bytes();
+    let is_timestamp = bytes.len() >= 20
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[10] == b'T'
+        && timestamp.ends_with('Z');
+    if is_timestamp { rest } else { line }
+}
+
+fn command_event(command: &str, properties: &str, message: &str) -> Option<LogEvent> {
+    let level = match command {
+        "group" => return Some(LogEvent::GroupStart { name: message.to_string() }),
+        "endgroup" => return Some(LogEvent::GroupEnd),
+        "error" => AnnotationLevel::Error,
+        "warning" => AnnotationLevel::Warning,
+        "notice" => AnnotationLevel::Notice,
+        _ => return None,
+    };
+
+    let mut annotation = Annotation {
+        level,
+        message: message.to_string(),
+        title: None,
+        file: None,
+        line: None,
+        column: None,
+    };
+    for property in properties.split(',') {
+        let Some((key, val)) = property.split_once('=') else {
+            continue;
+        };
+        let val = unescape(val.trim());
+        match key.trim() {
+            "title" => annotation.title = Some(val),
+            "file" => annotation.file = Some(val),
+            "line" => annotation.line = val.parse().ok(),
+            "col" => annotation.column = val.parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(LogEvent::Annotation(annotation))
+}
+
+// workflow commands escape the characters that would break their syntax
+fn unescape(text: &str) -> String {
+    text.replace("%0D", "\r")
+        .replace("%0A", "\n")
+        .replace("%3A", ":")
+        .replace("%2C", ",")
+        .replace("%25", "%")
+}
@@ -0,0 +1,119 @@
+use flate2::read::MultiGzDecoder;
+use std::{
+    collections::VecDeque, error::Error, fs::File, io::{self, BufRead, BufReader, Read, Seek, SeekFrom}, path::{Path, PathBuf}
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// which part of a log to read, so a huge log never has to fit in memory at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogSlice {
+    TailLines(usize),
+    // the last bytes of the log, starting at a line boundary
+    TailBytes(u64),
+    Bytes { start: u64, len: u64 },
+}
+
+// logs are decoded lossily, a stray binary byte in a CI log should not make it unreadable,
+// gzip compressed logs are recognised by their magic bytes and decompressed on the fly
+pub async fn read_log(path: impl Into<PathBuf>, slice: LogSlice) -> Result<String, Box<dyn Error>> {
+    let path = path.into();
+    let content = tokio::task::spawn_blocking(move || read_slice(&path, slice)).await??;
+    Ok(content)
+}
+
+fn read_slice(path: &Path, slice: LogSlice) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let gzip = is_gzip(&mut file)?;
+
+    // plain files can seek straight to the wanted bytes, compressed ones have to be streamed through
+    let bytes = match (slice, gzip) {
+        (LogSlice::TailBytes(max_bytes), false) => {
+            let size = file.metadata()?.len();
+            let start = size.saturating_sub(max_bytes);
+            file.seek(SeekFrom::Start(start))?;
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            from_line_boundary(bytes, start > 0)
+        }
+        (LogSlice::Bytes { start, len }, false) => {
+            file.seek(SeekFrom::Start(start))?;
+            let mut bytes = Vec::new();
+            file.take(len).read_to_end(&mut bytes)?;
+            bytes
+        }
+        (slice, gzip) => {
+            let reader: Box<dyn Read> = if gzip {
+                Box::new(MultiGzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            read_streamed(BufReader::new(reader), slice)?
+        }
+    };
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn read_streamed<R: BufRead>(mut reader: R, slice: LogSlice) -> io::Result<Vec<u8>> {
+    match slice {
+        LogSlice::TailLines(count) => {
+            let mut lines = VecDeque::with_capacity(count);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line)? > 0 {
+                if lines.len() == count {
+                    lines.pop_front();
+                }
+                if count > 0 {
+                    lines.push_back(std::mem::take(&mut line));
+                }
+                line.clear();
+            }
+            Ok(lines.into_iter().flatten().collect())
+        }
+        LogSlice::TailBytes(max_bytes) => {
+            let max_bytes = max_bytes as usize;
+            let mut tail = Vec::new();
+            let mut skipped = false;
+            let mut chunk = [0u8; 8192];
+            loop {
+                let read = reader.read(&mut chunk)?;
+                if read == 0 {
+                    break;
+                }
+                tail.extend_from_slice(&chunk[..read]);
+                // trimmed in batches, so the buffer stays below twice the wanted size
+                if tail.len() > 2 * max_bytes {
+                    tail.drain(..tail.len() - max_bytes);
+                    skipped = true;
+                }
+            }
+            if tail.len() > max_bytes {
+                tail.drain(..tail.len() - max_bytes);
+                skipped = true;
+            }
+            Ok(from_line_boundary(tail, skipped))
+        }
+        LogSlice::Bytes { start, len } => {
+            io::copy(&mut reader.by_ref().take(start), &mut io::sink())?;
+            let mut bytes = Vec::new();
+            reader.take(len).read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+    }
+}
+
+// a tail starting in the middle of a line drops that partial line
+fn from_line_boundary(mut bytes: Vec<u8>, truncated: bool) -> Vec<u8> {
+    if truncated && let Some(newline) = bytes.iter().position(|&byte| byte == b'\n') {
+        bytes.drain(..=newline);
+    }
+    bytes
+}
+
+fn is_gzip(file: &mut File) -> io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let gzip = file.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(gzip)
+}
@@ -0,0 +1,222 @@
+use tokio::{
+    io::{AsyncRead, AsyncReadExt}, process::Command, task::JoinHandle, time::timeout
+};
+use std::{
+    env::var, error::Error, fmt::{self, Display, Formatter}, path::PathBuf, process::Stdio, time::Duration
+};
+use tracing::warn;
+use crate::process_execution::sandbox::{
+    self, SandboxOptions
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+// enough for the usual toolchains to find their binaries and caches, anything else has to be allowed explicitly
+const DEFAULT_ALLOWED_ENV: [&str; 3] = ["PATH", "HOME", "LANG"];
+// output still held open by a leftover background process is not waited for longer than that
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+// what the agent is allowed to run, the command itself always comes as an argument vector and never goes through a shell
+#[derive(Debug, Clone)]
+pub struct CommandPolicy {
+    pub allowed_binaries: Vec<String>,
+    pub allowed_env: Vec<String>,
+    pub working_dir: Option<PathBuf>,
+    pub timeout: Duration,
+    pub max_output_bytes: usize,
+    pub sandbox: Option<SandboxOptions>,
+}
+
+#[derive(Debug)]
+pub struct CommandOutput {
+    // none when the process was killed by a signal, or on timeout
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    pub truncated: bool,
+}
+
+impl CommandPolicy {
+    // nothing can be run until COMMAND_ALLOWLIST names the binaries
+    pub fn from_env() -> Self {
+        let list = |name: &str| var(name)
+            .ok()
+            .map(|val| val.split(',').map(str::trim).filter(|item| !item.is_empty()).map(String::from).collect::<Vec<String>>());
+
+        CommandPolicy {
+            allowed_binaries: list("COMMAND_ALLOWLIST").unwrap_or_default(),
+            allowed_env: list("COMMAND_ENV_ALLOWLIST")
+                .unwrap_or_else(|| DEFAULT_ALLOWED_ENV.iter().map(|name| name.to_string()).collect()),
+            working_dir: var("COMMAND_WORKING_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from),
+            timeout: var("COMMAND_TIMEOUT_SECS")
+                .ok()
+                .and_then(|val| val.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_TIMEOUT),
+            max_output_bytes: var("COMMAND_MAX_OUTPUT_BYTES")
+                .ok()
+                .and_then(|val| val.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+            sandbox: SandboxOptions::from_env(),
+        }
+    }
+
+    // binaries are matched by exact name, so "cargo" does not allow "/tmp/cargo"
+    pub fn is_allowed(&self, program: &str) -> bool {
+        self.allowed_binaries.iter().any(|allowed| allowed == program)
+    }
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+impl Display for CommandOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.timed_out, self.exit_code) {
+            (true, _) => write!(f, "Command timed out and was killed")?,
+            (false, Some(code)) => write!(f, "Command exited with code {}", code)?,
+            (false, None) => write!(f, "Command was terminated by a signal")?,
+        }
+        if self.truncated {
+            write!(f, " (output truncated)")?;
+        }
+        if !self.stdout.is_empty() {
+            write!(f, "\nstdout:\n{}", self.stdout)?;
+        }
+        if !self.stderr.is_empty() {
+            write!(f, "\nstderr:\n{}", self.stderr)?;
+        }
+        Ok(())
+    }
+}
+
+// splits a command line into its argument vector the way a shell would, without expanding anything:
+// quotes group words, e.g. `git commit -m "fix it"`, and a backslash escapes the next character
+// outside single quotes
+pub fn split_command(command: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut argv = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('\''), c) => current.get_or_insert_default().push(c),
+            (_, '\\') => {
+                let Some(escaped) = chars.next() else {
+                    return Err(format!("Command '{}' ends with an escape", command).into());
+                };
+                current.get_or_insert_default().push(escaped);
+            }
+            (Some(_), c) => current.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => argv.extend(current.take()),
+            (None, c) => current.get_or_insert_default().push(c),
+        }
+    }
+    if let Some(open) = quote {
+        return Err(format!("Command '{}' has an unclosed {} quote", command, open).into());
+    }
+    argv.extend(current);
+    Ok(argv)
+}
+
+pub async fn run_command(policy: &CommandPolicy, argv: &[String]) -> Result<CommandOutput, Box<dyn Error>> {
+    let Some((program, args)) = argv.split_first() else {
+        return Err("No command given".into());
+    };
+    if !policy.is_allowed(program) {
+        return Err(format!("Command '{}' is not in COMMAND_ALLOWLIST", program).into());
+    }
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .env_clear()
+        .envs(policy.allowed_env.iter().filter_map(|name| var(name).ok().map(|val| (name, val))))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut sandbox = match &policy.sandbox {
+        Some(options) => Some(sandbox::apply(&mut command, options, policy.working_dir.as_deref())?),
+        None => {
+            if let Some(dir) = &policy.working_dir {
+                command.current_dir(dir);
+            }
+            None
+        }
+    };
+
+    let mut child = command.spawn()?;
+    if let Some(sandbox) = &mut sandbox {
+        sandbox.set_process_group(child.id());
+    }
+    let stdout = tokio::spawn(read_capped(child.stdout.take(), policy.max_output_bytes));
+    let stderr = tokio::spawn(read_capped(child.stderr.take(), policy.max_output_bytes));
+
+    let (status, timed_out) = match timeout(policy.timeout, child.wait()).await {
+        Ok(status) => (Some(status?), false),
+        Err(_) => {
+            warn!("Command '{}' exceeded {} seconds, killing it", program, policy.timeout.as_secs());
+            match &sandbox {
+                Some(sandbox) => sandbox.kill(),
+                None => child.start_kill()?,
+            }
+            child.wait().await?;
+            (None, true)
+        }
+    };
+    // whatever the command left running in its process group goes with it
+    drop(sandbox);
+
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated)) = (collect_output(stdout).await, collect_output(stderr).await);
+    Ok(CommandOutput {
+        exit_code: status.and_then(|status| status.code()),
+        stdout,
+        stderr,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+async fn collect_output(reader: JoinHandle<(String, bool)>) -> (String, bool) {
+    let abort = reader.abort_handle();
+    match timeout(OUTPUT_GRACE, reader).await {
+        Ok(Ok(output)) => output,
+        _ => {
+            abort.abort();
+            (String::new(), true)
+        }
+    }
+}
+
+// keeps draining the pipe past the cap, otherwise a chatty process blocks on a full pipe until the timeout
+async fn read_capped<R: AsyncRead + Unpin>(pipe: Option<R>, max_bytes: usize) -> (String, bool) {
+    let Some(mut pipe) = pipe else {
+        return (String::new(), false);
+    };
+    let mut output = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let mut truncated = false;
+
+    while let Ok(read) = pipe.read(&mut chunk).await {
+        if read == 0 {
+            break;
+        }
+        let room = max_bytes.saturating_sub(output.len());
+        if read > room {
+            truncated = true;
+        }
+        output.extend_from_slice(&chunk[..read.min(room)]);
+    }
+
+    (String::from_utf8_lossy(&output).into_owned(), truncated)
+}
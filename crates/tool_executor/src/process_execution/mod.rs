@@ -5,9 +5,10 @@ use std::{
     error::Error, path::PathBuf
 };
 
-pub async fn read_file(file_name: PathBuf) -> Result<String, Box<dyn Error>> {
-    let file_name = PathBuf::from(file_name);
+pub mod command;
+pub mod sandbox;
 
+pub async fn read_file(file_name: PathBuf) -> Result<String, Box<dyn Error>> {
     let mut file = OpenOptions::new()
         .read(true)
         .open(file_name)
@@ -17,4 +18,4 @@ pub async fn read_file(file_name: PathBuf) -> Result<String, Box<dyn Error>> {
     file.read_to_string(&mut content).await?;
 
     Ok(content)
-}
\ No newline at end of file
+}
@@ -0,0 +1,114 @@
+use tokio::process::Command;
+use tempfile::TempDir;
+#[cfg(target_os = "linux")]
+use process_sandbox::{
+    ChildSetup, Namespaces, ReadOnlyMount
+};
+use std::{
+    env::var, error::Error, path::Path, time::Duration
+};
+
+// resource limits and isolation for the commands run by the agent, only implemented for Linux
+#[derive(Debug, Clone)]
+pub struct SandboxOptions {
+    pub cpu_time: Option<Duration>,
+    pub memory_bytes: Option<u64>,
+    pub working_dir: SandboxDir,
+    // the command gets its own network namespace with nothing but a loopback device that is down
+    pub isolate_network: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxDir {
+    // an empty directory created for the command and removed afterwards
+    Temp,
+    // the configured working directory, mounted read-only for the command
+    ReadOnly,
+}
+
+// keeps the temporary working directory alive while the command runs,
+// and kills whatever is left of its process group once dropped, also when the agent cancels the command
+pub struct SandboxGuard {
+    process_group: Option<u32>,
+    _temp_dir: Option<TempDir>,
+}
+
+impl SandboxOptions {
+    // the sandbox is opt-in with COMMAND_SANDBOX=true
+    pub fn from_env() -> Option<Self> {
+        if var("COMMAND_SANDBOX").ok()?.to_lowercase() != "true" {
+            return None;
+        }
+
+        Some(SandboxOptions {
+            cpu_time: var("COMMAND_CPU_SECS").ok().and_then(|val| val.parse::<u64>().ok()).map(Duration::from_secs),
+            // a limit too large to count in bytes is as good as no limit
+            memory_bytes: var("COMMAND_MEMORY_MB").ok().and_then(|val| val.parse::<u64>().ok()).map(|mb| mb.saturating_mul(1024 * 1024)),
+            working_dir: match var("COMMAND_SANDBOX_DIR").as_deref() {
+                Ok("read-only") => SandboxDir::ReadOnly,
+                _ => SandboxDir::Temp,
+            },
+            isolate_network: var("COMMAND_ISOLATE_NETWORK").is_ok_and(|val| val.to_lowercase() == "true"),
+        })
+    }
+}
+
+impl SandboxGuard {
+    pub fn set_process_group(&mut self, pid: Option<u32>) {
+        self.process_group = pid;
+    }
+
+    pub fn kill(&self) {
+        #[cfg(target_os = "linux")]
+        if let Some(pid) = self.process_group {
+            process_sandbox::kill_process_group(pid);
+        }
+    }
+}
+
+impl Drop for SandboxGuard {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_command: &mut Command, _options: &SandboxOptions, _working_dir: Option<&Path>) -> Result<SandboxGuard, Box<dyn Error>> {
+    Err("The command sandbox is only available on Linux".into())
+}
+
+// must be called before the command is spawned, everything but the temporary directory is set up in the forked child
+#[cfg(target_os = "linux")]
+pub fn apply(command: &mut Command, options: &SandboxOptions, working_dir: Option<&Path>) -> Result<SandboxGuard, Box<dyn Error>> {
+    command.process_group(0);
+
+    let mut temp_dir = None;
+    let mut read_only = None;
+    match options.working_dir {
+        SandboxDir::Temp => {
+            let dir = tempfile::Builder::new().prefix("devops-agent-command-").tempdir()?;
+            command.current_dir(dir.path());
+            temp_dir = Some(dir);
+        }
+        SandboxDir::ReadOnly => {
+            let dir = match working_dir {
+                Some(dir) => dir.canonicalize()?,
+                None => std::env::current_dir()?,
+            };
+            command.current_dir(&dir);
+            read_only = Some(ReadOnlyMount::new(&dir)?);
+        }
+    }
+
+    process_sandbox::setup_child(command, ChildSetup {
+        namespaces: Namespaces::new(read_only.is_some(), options.isolate_network),
+        read_only,
+        cpu_seconds: options.cpu_time.map(|cpu_time| cpu_time.as_secs().max(1)),
+        memory_bytes: options.memory_bytes,
+    });
+
+    Ok(SandboxGuard {
+        process_group: None,
+        _temp_dir: temp_dir,
+    })
+}
@@ -0,0 +1,124 @@
+use quick_xml::{
+    events::{BytesStart, Event}, Reader
+};
+use std::{
+    fmt::{self, Display, Formatter}, fs, path::Path
+};
+use crate::test_reports::{
+    parse_junit, TestStatus
+};
+
+// test and coverage reports found among the workflow artifacts, reduced to what matters for the analysis
+#[derive(Debug, PartialEq)]
+pub enum ReportSummary {
+    JUnit {
+        tests: usize,
+        failures: usize,
+        skipped: usize,
+        failed_cases: Vec<String>,
+    },
+    Coverage {
+        format: &'static str,
+        line_percent: f64,
+    },
+}
+
+impl Display for ReportSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportSummary::JUnit { tests, failures, skipped, failed_cases } => {
+                write!(f, "JUnit report: {} tests, {} failed, {} skipped", tests, failures, skipped)?;
+                for case in failed_cases {
+                    write!(f, "\n  failed: {}", case)?;
+                }
+                Ok(())
+            }
+            ReportSummary::Coverage { format, line_percent } => write!(f, "{} coverage report: {:.1}% of lines covered", format, line_percent),
+        }
+    }
+}
+
+// files which are not recognised as reports, or cannot be read, are skipped
+pub fn summarize_report(path: &Path) -> Option<ReportSummary> {
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+    let content = fs::read_to_string(path).ok()?;
+    summarize_report_content(&file_name, &content)
+}
+
+pub fn summarize_report_content(file_name: &str, content: &str) -> Option<ReportSummary> {
+    if file_name.ends_with(".xml") {
+        if content.contains("<testsuite") {
+            return summarize_junit(content);
+        }
+        if content.contains("<coverage") {
+            return summarize_cobertura(content);
+        }
+    }
+    if (file_name.ends_with(".info") || file_name.contains("lcov")) && content.contains("end_of_record") {
+        return summarize_lcov(content);
+    }
+    if file_name.ends_with(".json") && file_name.contains("coverage-summary") {
+        return summarize_istanbul(content);
+    }
+    None
+}
+
+fn summarize_junit(content: &str) -> Option<ReportSummary> {
+    let report = parse_junit(content)?;
+    Some(ReportSummary::JUnit {
+        tests: report.cases.len(),
+        failures: report.count(TestStatus::Failed),
+        skipped: report.count(TestStatus::Skipped),
+        failed_cases: report.failed_cases().map(|case| case.name.clone()).collect(),
+    })
+}
+
+fn summarize_cobertura(content: &str) -> Option<ReportSummary> {
+    let mut reader = Reader::from_str(content);
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"coverage" => {
+                let line_rate = attribute(&e, "line-rate")?.parse::<f64>().ok()?;
+                return Some(ReportSummary::Coverage {
+                    format: "Cobertura",
+                    line_percent: line_rate * 100.0,
+                });
+            }
+            Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+fn summarize_lcov(content: &str) -> Option<ReportSummary> {
+    let (mut found, mut hit) = (0u64, 0u64);
+    for line in content.lines() {
+        if let Some(val) = line.strip_prefix("LF:") {
+            found += val.trim().parse::<u64>().ok()?;
+        } else if let Some(val) = line.strip_prefix("LH:") {
+            hit += val.trim().parse::<u64>().ok()?;
+        }
+    }
+    if found == 0 {
+        return None;
+    }
+    Some(ReportSummary::Coverage {
+        format: "LCOV",
+        line_percent: hit as f64 * 100.0 / found as f64,
+    })
+}
+
+// coverage-summary.json written by istanbul/nyc/jest
+fn summarize_istanbul(content: &str) -> Option<ReportSummary> {
+    let summary = serde_json::from_str::<serde_json::Value>(content).ok()?;
+    let line_percent = summary.get("total")?.get("lines")?.get("pct")?.as_f64()?;
+    Some(ReportSummary::Coverage {
+        format: "Istanbul",
+        line_percent,
+    })
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    let attribute = element.try_get_attribute(name).ok()??;
+    Some(attribute.unescape_value().ok()?.into_owned())
+}
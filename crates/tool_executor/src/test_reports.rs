@@ -0,0 +1,319 @@
+use quick_xml::{
+    events::{BytesStart, Event}, Reader
+};
+use serde_json::Value;
+use std::fmt::{
+    self, Display, Formatter
+};
+
+// results of JUnit XML reports, cargo test (pretty and json) and pytest output, in one shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    // the JUnit suite, the cargo test target or the pytest file
+    pub suite: Option<String>,
+    // as printed by the test runner, e.g. "api::login" or "tests/test_db.py::test_insert"
+    pub name: String,
+    pub status: TestStatus,
+    pub duration_secs: Option<f64>,
+    pub failure_message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestReport {
+    pub cases: Vec<TestCase>,
+}
+
+impl TestReport {
+    pub fn count(&self, status: TestStatus) -> usize {
+        self.cases.iter().filter(|case| case.status == status).count()
+    }
+
+    pub fn failed_cases(&self) -> impl Iterator<Item = &TestCase> {
+        self.cases.iter().filter(|case| case.status == TestStatus::Failed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    // a later result of the same test, e.g. from the pytest summary, completes the earlier one
+    fn push(&mut self, case: TestCase) {
+        match self.cases.iter_mut().find(|existing| existing.name == case.name && existing.suite == case.suite) {
+            Some(existing) => {
+                existing.status = case.status;
+                existing.duration_secs = case.duration_secs.or(existing.duration_secs);
+                existing.failure_message = case.failure_message.or(existing.failure_message.take());
+            }
+            None => self.cases.push(case),
+        }
+    }
+}
+
+impl Display for TestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tests, {} failed, {} skipped", self.cases.len(), self.count(TestStatus::Failed), self.count(TestStatus::Skipped))?;
+        for case in self.failed_cases() {
+            write!(f, "\n  failed: {}", case.name)?;
+            if let Some(message) = case.failure_message.as_deref().and_then(|message| message.lines().find(|line| !line.trim().is_empty())) {
+                write!(f, " ({})", message.trim())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TestCase {
+    fn new(suite: Option<String>, name: &str, status: TestStatus) -> Self {
+        TestCase {
+            suite,
+            name: name.trim().to_string(),
+            status,
+            duration_secs: None,
+            failure_message: None,
+        }
+    }
+}
+
+pub fn parse_junit(content: &str) -> Option<TestReport> {
+    let mut reader = Reader::from_str(content);
+    let mut report = TestReport::default();
+    let mut suite = None;
+    let mut current: Option<TestCase> = None;
+    let mut in_failure = false;
+
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) if e.name().as_ref() == b"testsuite" => suite = attribute(&e, "name"),
+            Event::Empty(e) if e.name().as_ref() == b"testcase" => report.push(junit_case(&e, &suite)),
+            Event::Start(e) if e.name().as_ref() == b"testcase" => current = Some(junit_case(&e, &suite)),
+            Event::Start(e) if is_failure(&e) => {
+                mark_failed(current.as_mut(), &e);
+                in_failure = true;
+            }
+            Event::Empty(e) if is_failure(&e) => mark_failed(current.as_mut(), &e),
+            Event::Text(text) if in_failure => {
+                if let Some(case) = current.as_mut() && case.failure_message.is_none() {
+                    case.failure_message = text.decode().ok().map(|text| text.trim().to_string()).filter(|text| !text.is_empty());
+                }
+            }
+            Event::End(e) if matches!(e.name().as_ref(), b"failure" | b"error") => in_failure = false,
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"skipped" => {
+                if let Some(case) = current.as_mut() {
+                    case.status = TestStatus::Skipped;
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"testcase" => {
+                if let Some(case) = current.take() {
+                    report.push(case);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Some(report)
+}
+
+fn is_failure(element: &BytesStart) -> bool {
+    matches!(element.name().as_ref(), b"failure" | b"error")
+}
+
+// a case may report both a failure and an error, the first message is kept
+fn mark_failed(case: Option<&mut TestCase>, element: &BytesStart) {
+    if let Some(case) = case {
+        case.status = TestStatus::Failed;
+        if case.failure_message.is_none() {
+            case.failure_message = attribute(element, "message");
+        }
+    }
+}
+
+fn junit_case(element: &BytesStart, suite: &Option<String>) -> TestCase {
+    let name = attribute(element, "name").unwrap_or_default();
+    let name = match attribute(element, "classname") {
+        Some(class) if !class.is_empty() => format!("{}::{}", class, name),
+        _ => name,
+    };
+    let mut case = TestCase::new(suite.clone(), &name, TestStatus::Passed);
+    case.duration_secs = attribute(element, "time").and_then(|time| time.parse().ok());
+    case
+}
+
+// the human readable libtest output, with the panic messages of the "---- name stdout ----" sections
+pub fn parse_cargo_test(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    let mut suite = None;
+    let mut failure: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some((name, lines)) = failure.take_if(|_| trimmed.starts_with("---- ") || trimmed == "failures:") {
+            set_failure_message(&mut report, &name, &lines);
+        }
+        if let Some((_, lines)) = failure.as_mut() {
+            if !trimmed.starts_with("note: run with `RUST_BACKTRACE") {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        if let Some(target) = trimmed.strip_prefix("Running ") {
+            suite = Some(target.split(" (").next().unwrap_or(target).to_string());
+        } else if trimmed.starts_with("Doc-tests ") {
+            suite = Some(trimmed.to_string());
+        } else if let Some(name) = trimmed.strip_prefix("---- ").and_then(|rest| rest.strip_suffix(" stdout ----")) {
+            failure = Some((name.to_string(), Vec::new()));
+        } else if let Some(case) = cargo_test_line(trimmed, &suite) {
+            report.push(case);
+        }
+    }
+    if let Some((name, lines)) = failure {
+        set_failure_message(&mut report, &name, &lines);
+    }
+
+    report
+}
+
+// "test api::login ... ok", optionally followed by the time with --report-time, e.g. "ok <0.012s>"
+fn cargo_test_line(line: &str, suite: &Option<String>) -> Option<TestCase> {
+    let (name, result) = line.strip_prefix("test ")?.split_once(" ... ")?;
+    let (result, time) = result.split_once(" <").unwrap_or((result, ""));
+    let status = match result {
+        "ok" => TestStatus::Passed,
+        "FAILED" => TestStatus::Failed,
+        result if result.starts_with("ignored") => TestStatus::Skipped,
+        _ => return None,
+    };
+
+    let mut case = TestCase::new(suite.clone(), name, status);
+    case.duration_secs = time.strip_suffix("s>").and_then(|time| time.parse().ok());
+    Some(case)
+}
+
+fn set_failure_message(report: &mut TestReport, name: &str, lines: &[&str]) {
+    let message = lines.join("\n").trim().to_string();
+    if let Some(case) = report.cases.iter_mut().rev().find(|case| case.name == name) && !message.is_empty() {
+        case.failure_message = Some(message);
+    }
+}
+
+// `cargo test -- -Z unstable-options --format json`, one event per line
+pub fn parse_libtest_json(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+
+    for line in output.lines() {
+        let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if event.get("type").and_then(Value::as_str) != Some("test") {
+            continue;
+        }
+        let status = match event.get("event").and_then(Value::as_str) {
+            Some("ok") => TestStatus::Passed,
+            Some("failed") | Some("timeout") => TestStatus::Failed,
+            Some("ignored") => TestStatus::Skipped,
+            _ => continue,
+        };
+        let Some(name) = event.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+
+        let mut case = TestCase::new(None, name, status);
+        case.duration_secs = event.get("exec_time").and_then(Value::as_f64);
+        case.failure_message = event
+            .get("stdout")
+            .or_else(|| event.get("message"))
+            .and_then(Value::as_str)
+            .filter(|_| status == TestStatus::Failed)
+            .map(|message| message.trim().to_string());
+        report.push(case);
+    }
+
+    report
+}
+
+// `pytest -v` result lines and the "short test summary info" lines carrying the failure messages
+pub fn parse_pytest(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+
+    for line in output.lines() {
+        let mut words = line.trim().splitn(2, ' ');
+        let (first, rest) = (words.next().unwrap_or_default(), words.next().unwrap_or_default().trim());
+
+        let (node_id, status, message) = match first {
+            "FAILED" | "ERROR" => {
+                let (node_id, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+                (node_id, TestStatus::Failed, Some(message).filter(|message| !message.is_empty()))
+            }
+            node_id if node_id.contains("::") => {
+                let status = match rest.split_whitespace().next() {
+                    Some("PASSED") | Some("XFAIL") => TestStatus::Passed,
+                    Some("FAILED") | Some("ERROR") | Some("XPASS") => TestStatus::Failed,
+                    Some("SKIPPED") => TestStatus::Skipped,
+                    _ => continue,
+                };
+                (node_id, status, None)
+            }
+            _ => continue,
+        };
+        if !node_id.contains("::") {
+            continue;
+        }
+
+        let suite = node_id.split("::").next().map(String::from);
+        let mut case = TestCase::new(suite, node_id, status);
+        case.failure_message = message.map(String::from);
+        report.push(case);
+    }
+
+    report
+}
+
+// `go test -v` result lines
+fn parse_go_test(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        let (status, rest) = if let Some(rest) = line.strip_prefix("--- PASS: ") {
+            (TestStatus::Passed, rest)
+        } else if let Some(rest) = line.strip_prefix("--- FAIL: ") {
+            (TestStatus::Failed, rest)
+        } else if let Some(rest) = line.strip_prefix("--- SKIP: ") {
+            (TestStatus::Skipped, rest)
+        } else {
+            continue;
+        };
+        let (name, time) = rest.split_once(" (").unwrap_or((rest, ""));
+        let mut case = TestCase::new(None, name, status);
+        case.duration_secs = time.strip_suffix("s)").and_then(|time| time.parse().ok());
+        report.push(case);
+    }
+
+    report
+}
+
+// CI logs mix the output of several runners, so every parser gets its chance
+pub fn parse_test_output(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    for parsed in [parse_libtest_json(output), parse_cargo_test(output), parse_go_test(output), parse_pytest(output)] {
+        for case in parsed.cases {
+            report.push(case);
+        }
+    }
+    report
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    let attribute = element.try_get_attribute(name).ok()??;
+    Some(attribute.unescape_value().ok()?.into_owned())
+}
@@ -0,0 +1,141 @@
+use tool_executor::ci_providers::{
+    gitlab::GitlabProvider, jenkins::JenkinsProvider, CiProvider
+};
+use wiremock::{
+    matchers::{body_string, header, method, path, query_param}, Mock, MockServer, ResponseTemplate
+};
+
+#[tokio::test]
+async fn test_gitlab_pipelines_use_github_conclusions() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/group%2Fproject/pipelines"))
+        .and(header("private-token", "token"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"[
+            {"id": 7, "status": "failed", "ref": "main", "sha": "abc", "web_url": "https://gitlab/p/7"},
+            {"id": 6, "status": "running", "ref": "main", "sha": "abd", "web_url": "https://gitlab/p/6"}
+        ]"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let provider = GitlabProvider::new(&server.uri(), "token");
+    let runs = provider.list_runs("group/project").await.unwrap();
+
+    assert_eq!((runs[0].status.as_str(), runs[0].conclusion.as_deref()), ("completed", Some("failure")));
+    assert_eq!((runs[1].status.as_str(), runs[1].conclusion.as_deref()), ("running", None));
+    assert_eq!(runs[0].branch.as_deref(), Some("main"));
+}
+
+#[tokio::test]
+async fn test_gitlab_pipeline_logs_join_job_traces() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/group%2Fproject/pipelines/7/jobs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"[
+            {"id": 1, "name": "build", "stage": "build", "status": "success"},
+            {"id": 2, "name": "test", "stage": "test", "status": "failed"}
+        ]"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/group%2Fproject/jobs/1/trace"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("compiled\n"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/group%2Fproject/jobs/2/trace"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("test failed\n"))
+        .mount(&server)
+        .await;
+
+    let provider = GitlabProvider::new(&server.uri(), "token");
+    let logs = provider.fetch_run_logs("group/project", 7).await.unwrap();
+
+    assert!(logs.find("compiled").unwrap() < logs.find("== job test").unwrap());
+    assert!(logs.contains("test failed"));
+}
+
+#[tokio::test]
+async fn test_jenkins_builds_and_progressive_console() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/job/backend/job/api/api/json"))
+        .and(header("authorization", "Basic YWdlbnQ6dG9rZW4="))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"builds": [
+            {"number": 12, "result": null, "building": true, "url": "https://jenkins/12", "actions": [{}]},
+            {"number": 11, "result": "UNSTABLE", "building": false, "url": "https://jenkins/11", "actions": [
+                {}, {"lastBuiltRevision": {"SHA1": "abc", "branch": [{"name": "origin/main"}]}}
+            ]}
+        ]}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job/backend/job/api/11/logText/progressiveText"))
+        .and(query_param("start", "0"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-text-size", "9").insert_header("x-more-data", "true").set_body_string("building\n"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job/backend/job/api/11/logText/progressiveText"))
+        .and(query_param("start", "9"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-text-size", "21").set_body_string("tests failed\n"))
+        .mount(&server)
+        .await;
+
+    let provider = JenkinsProvider::new(&server.uri(), "agent", "token");
+    let runs = provider.list_runs("backend/api").await.unwrap();
+    let logs = provider.fetch_run_logs("backend/api", 11).await.unwrap();
+
+    assert_eq!((runs[0].status.as_str(), runs[0].conclusion.as_deref()), ("in_progress", None));
+    assert_eq!(runs[1].conclusion.as_deref(), Some("failure"));
+    assert_eq!((runs[1].head_sha.as_str(), runs[1].branch.as_deref()), ("abc", Some("origin/main")));
+    assert_eq!(logs, "building\ntests failed\n");
+}
+
+#[tokio::test]
+async fn test_jenkins_retry_reruns_the_requested_build() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/job/api/11/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"actions": [{}, {"parameters": [
+            {"name": "BRANCH", "value": "release/1.2"}, {"name": "DEPLOY", "value": false}
+        ]}]}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/job/api/buildWithParameters"))
+        .and(body_string("BRANCH=release%2F1.2&DEPLOY=false"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job/api/12/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"actions": [{}]}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/job/api/12/replay/rebuild"))
+        .respond_with(ResponseTemplate::new(302))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job/api/13/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"actions": []}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job/api/14/api/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"actions": [{"parameters": [{"name": "TOKEN"}]}]}"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let provider = JenkinsProvider::new(&server.uri(), "agent", "token");
+    provider.retry_run("api", 11).await.unwrap();
+    provider.retry_run("api", 12).await.unwrap();
+    let error = provider.retry_run("api", 13).await.unwrap_err();
+    assert_eq!(error.to_string(), "Build 13 of api has no parameters to copy and cannot be replayed, only pipeline builds can be");
+    let error = provider.retry_run("api", 14).await.unwrap_err();
+    assert!(error.to_string().contains("parameter 'TOKEN'"), "{}", error);
+}
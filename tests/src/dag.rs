@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+use agent_core::{
+    agent_structs::{
+        Agent, AgentInput, AgentStatus, DevOpsAgent, Repository, Step
+    },
+    dag::{
+        StepGraph, StepState
+    },
+    pipeline::{
+        parse_pipeline_file, PipelineFormat
+    },
+    tool_args::{
+        parse_pipeline, ToolArgs
+    }
+};
+
+fn step(id: &str, needs: &[&str]) -> Step {
+    let mut step = Step::new("notify", ToolArgs::default());
+    step.id = id.to_string();
+    step.needs = needs.iter().map(|needed| needed.to_string()).collect();
+    step
+}
+
+#[test]
+fn test_failed_steps_cancel_their_dependants_only() {
+    let steps = vec![
+        step("list", &[]),
+        step("classify", &["list"]),
+        step("diff", &["classify"]),
+        step("report", &["diff", "list"]),
+        step("durations", &[]),
+    ];
+    let mut graph = StepGraph::new(&steps).unwrap();
+    assert_eq!(graph.ready(), vec![0, 4]);
+
+    graph.start(0);
+    graph.start(4);
+    assert!(graph.ready().is_empty());
+    graph.finish(0, StepState::Succeeded);
+    assert_eq!(graph.ready(), vec![1]);
+
+    graph.start(1);
+    graph.finish(1, StepState::Failed(String::from("Timed out after 600s")));
+    assert_eq!(graph.state(2), &StepState::Cancelled(String::from("'classify' failed")));
+    assert_eq!(graph.state(3), &StepState::Cancelled(String::from("'diff' was cancelled")));
+    assert!(!graph.is_finished());
+
+    graph.finish(4, StepState::Skipped);
+    assert!(graph.is_finished());
+    assert_eq!(graph.failed(), vec!["classify"]);
+    assert_eq!(graph.succeeded(), vec!["list"]);
+    assert_eq!(graph.to_string(), "list: succeeded
+classify (needs list): failed: Timed out after 600s
+diff (needs classify): cancelled: 'classify' failed
+report (needs diff, list): cancelled: 'diff' was cancelled
+durations: skipped");
+}
+
+#[test]
+fn test_steps_only_need_earlier_steps() {
+    let error = StepGraph::new(&[step("list", &["classify"]), step("classify", &[])]).unwrap_err();
+    assert_eq!(error.to_string(), "Step 'list' needs 'classify', which is not defined before it");
+
+    // a skipped step lets the steps needing it run
+    let mut graph = StepGraph::new(&[step("list", &[]), step("classify", &["list"])]).unwrap();
+    graph.start(0);
+    graph.finish(0, StepState::Skipped);
+    assert_eq!(graph.ready(), vec![1]);
+}
+
+#[test]
+fn test_pipelines_run_in_order_unless_steps_declare_needs() {
+    let steps = parse_pipeline("list_workflows classify_failures list_workflows diff_failed_logs").unwrap();
+    let needs = steps.iter().map(|step| step.needs.clone()).collect::<Vec<Vec<String>>>();
+    assert_eq!(needs, vec![vec![], vec![String::from("list_workflows")], vec![String::from("classify_failures")], vec![String::from("list_workflows")]]);
+
+    let yaml = "steps:
+  - tool: list_workflows
+  - tool: track_durations
+    needs: []
+  - tool: classify_failures
+    needs: [list_workflows]
+  - tool: diff_failed_logs
+    args:
+      run_ids: ${steps.list_workflows.failed_run_ids}
+";
+    let steps = parse_pipeline_file(yaml, PipelineFormat::Yaml).unwrap();
+    assert!(steps[1].needs.is_empty());
+    assert_eq!(steps[2].needs, vec!["list_workflows"]);
+    // the step before it and the one it takes outputs from
+    assert_eq!(steps[3].needs, vec!["classify_failures", "list_workflows"]);
+
+    let cases = [
+        (yaml.replace("needs: [list_workflows]", "needs: [diff_failed_logs]"), "line 6: Step 3 'classify_failures': Needs 'diff_failed_logs', which is not defined before it"),
+        (
+            format!("{}    on_failure:\n      - tool: notify\n        needs: [track_durations]\n", yaml),
+            "line 12: Step 4 'diff_failed_logs', on_failure step 1 'notify': on_failure steps run right after their step, they cannot have needs",
+        ),
+    ];
+    for (content, error) in cases {
+        assert_eq!(parse_pipeline_file(&content, PipelineFormat::Yaml).unwrap_err().to_string(), error);
+    }
+}
+
+#[tokio::test]
+async fn test_independent_steps_run_after_a_failure() {
+    // git_blame fails right away on a checkout that does not exist, notify always succeeds
+    let yaml = "steps:
+  - id: start
+    tool: notify
+  - id: blame
+    tool: git_blame
+    needs: []
+    args: { checkout: /nonexistent/checkout, file: src/main.rs, line: 1 }
+  - id: after_blame
+    tool: notify
+  - id: last
+    tool: notify
+  - id: after_start
+    tool: notify
+    needs: [start]
+";
+    let repository = Repository {
+        owner: String::from("owner"),
+        name: String::from("repo"),
+        data_dir: PathBuf::from("logs"),
+    };
+    let mut agent = DevOpsAgent::new(parse_pipeline_file(yaml, PipelineFormat::Yaml).unwrap(), repository);
+    agent.max_parallel_steps = 2;
+
+    let result = agent.handle_input(AgentInput { message: String::new(), context: None }).await;
+
+    assert!(matches!(result.status, AgentStatus::Error(ref error) if error == "Steps failed: blame"), "{}", result.output);
+    let lines = result.output.lines().collect::<Vec<&str>>();
+    assert_eq!(lines[0], "Failed steps: blame");
+    assert_eq!(lines[1], "start: succeeded");
+    assert!(lines[2].starts_with("blame: failed: "), "{}", lines[2]);
+    assert_eq!(&lines[3..], ["after_blame (needs blame): cancelled: 'blame' failed", "last (needs after_blame): cancelled: 'after_blame' was cancelled", "after_start (needs start): succeeded"]);
+}
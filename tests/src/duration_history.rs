@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use memory_store::duration_history::DurationHistory;
+use tool_executor::github_interaction::{
+    github_http_client::GithubClient, github_jobs::{duration_secs, list_run_jobs}
+};
+use wiremock::{
+    matchers::{method, path}, Mock, MockServer, ResponseTemplate
+};
+
+const DAY: i64 = 24 * 60 * 60;
+
+#[test]
+fn test_slow_run_is_compared_with_median_of_window() {
+    let mut history = DurationHistory::default();
+    // an old slow run falls out of the window
+    history.record("main_ci / build", 1, 0, 900.0);
+    for (run_id, secs) in [(2, 100.0), (3, 110.0), (4, 90.0), (5, 100.0), (6, 105.0)] {
+        history.record("main_ci / build", run_id, 20 * DAY + run_id as i64, secs);
+    }
+    history.record("main_ci / build", 7, 30 * DAY, 150.0);
+    history.record("main_ci / build", 7, 30 * DAY, 10.0);
+    history.record("main_ci / lint", 7, 30 * DAY, 500.0);
+
+    assert_eq!(history.median_before("main_ci / build", 30 * DAY, 14 * DAY), Some(100.0));
+    let regressions = history.regressions(&HashSet::from([7]), 14 * DAY, 40.0);
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].key, "main_ci / build");
+    assert_eq!(regressions[0].increase_percent, 50.0);
+    assert_eq!(regressions[0].to_string(), "main_ci / build took 2m 30s in run 7, 50% slower than its median of 1m 40s");
+    assert!(history.regressions(&HashSet::from([7]), 14 * DAY, 60.0).is_empty());
+}
+
+#[tokio::test]
+async fn test_job_and_step_durations_from_github() {
+    let server = MockServer::start().await;
+    let body = r#"{"jobs": [{"id": 1, "name": "build", "status": "completed", "conclusion": "success",
+        "started_at": "2025-10-01T10:00:00Z", "completed_at": "2025-10-01T10:05:30Z",
+        "steps": [{"name": "Run tests", "number": 3, "conclusion": "success", "started_at": "2025-10-01T10:01:00Z", "completed_at": "2025-10-01T10:04:00Z"},
+                  {"name": "Deploy", "number": 4, "conclusion": "skipped", "started_at": null, "completed_at": null}]}]}"#;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/101/jobs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let jobs = list_run_jobs(&client, "owner", "repo", 101).await.unwrap();
+
+    assert_eq!(duration_secs(jobs[0].started_at, jobs[0].completed_at), Some(330.0));
+    assert_eq!(duration_secs(jobs[0].steps[0].started_at, jobs[0].steps[0].completed_at), Some(180.0));
+    assert_eq!(duration_secs(jobs[0].steps[1].started_at, jobs[0].steps[1].completed_at), None);
+}
@@ -0,0 +1,52 @@
+use tool_executor::error_extraction::{
+    classify_line, extract_errors, ErrorKind
+};
+
+const CARGO_LOG: &str = "   Compiling app v0.1.0
+warning: unused variable
+error[E0308]: mismatched types
+ --> src/main.rs:4:18
+  |
+4 |     let x: u32 = \"a\";
+  |            ---   ^^^ expected `u32`, found `&str`
+error: could not compile `app` (bin \"app\") due to 1 previous error
+step a
+step b
+step c
+step d
+step e
+step f
+step g
+Process completed with exit code 101.";
+
+#[test]
+fn test_classify_error_lines() {
+    let cases = [
+        ("thread 'main' panicked at src/main.rs:2:5:", Some(ErrorKind::Panic)),
+        ("test tests::add ... FAILED", Some(ErrorKind::FailedTest)),
+        ("FAILED tests/test_api.py::test_login - AssertionError", Some(ErrorKind::FailedTest)),
+        ("--- FAIL: TestLogin (0.00s)", Some(ErrorKind::FailedTest)),
+        ("npm ERR! code ELIFECYCLE", Some(ErrorKind::Npm)),
+        ("ERROR: Could not find a version that satisfies the requirement foo", Some(ErrorKind::Python)),
+        ("[ERROR] Failed to execute goal org.apache.maven.plugins", Some(ErrorKind::Maven)),
+        ("Process completed with exit code 0.", None),
+        ("test tests::add ... ok", None),
+    ];
+
+    for (line, kind) in cases {
+        assert_eq!(classify_line(line), kind, "{}", line);
+    }
+}
+
+#[test]
+fn test_extract_error_windows() {
+    let excerpts = extract_errors(CARGO_LOG);
+
+    assert_eq!(excerpts.len(), 2);
+    assert_eq!((excerpts[0].kind, excerpts[0].line_number), (ErrorKind::Compiler, 3));
+    // the "could not compile" line falls into the window of the diagnostic
+    assert_eq!(excerpts[0].lines.first().map(String::as_str), Some("   Compiling app v0.1.0"));
+    assert!(excerpts[0].lines.iter().any(|line| line.starts_with("error: could not compile")));
+    assert_eq!((excerpts[1].kind, excerpts[1].headline.as_str()), (ErrorKind::ExitCode, "Process completed with exit code 101."));
+    assert!(!excerpts[1].lines.iter().any(|line| excerpts[0].lines.contains(line)));
+}
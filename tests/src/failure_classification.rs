@@ -0,0 +1,67 @@
+use memory_store::test_history::TestHistory;
+use tool_executor::failure_classification::{
+    classify_failure, test_outcomes, FailureClass, TestOutcome
+};
+
+#[test]
+fn test_classify_failed_runs() {
+    let cases = [
+        (Some("cancelled"), "##[error]The job running on runner X has exceeded the maximum execution time of 10 minutes.", FailureClass::Timeout),
+        (Some("cancelled"), "Cancelling the run", FailureClass::Cancelled),
+        (Some("failure"), "##[error]The runner has received a shutdown signal.\ntest a ... FAILED", FailureClass::Infrastructure),
+        (Some("failure"), "error: failed to get `serde` as a dependency\nCould not resolve host: index.crates.io", FailureClass::Dependency),
+        (Some("failure"), "error[E0425]: cannot find value `x` in this scope", FailureClass::Compile),
+        (Some("failure"), "test api::login ... FAILED\ntest result: FAILED. 1 passed; 1 failed", FailureClass::Test),
+        (Some("failure"), "something went wrong", FailureClass::Unknown),
+    ];
+
+    for (conclusion, logs, class) in cases {
+        assert_eq!(classify_failure(conclusion, logs), class, "{}", logs);
+    }
+    assert!(FailureClass::Dependency.is_transient() && !FailureClass::Dependency.needs_issue());
+    assert!(FailureClass::Test.needs_issue() && !FailureClass::Test.is_transient());
+}
+
+#[test]
+fn test_outcomes_of_test_runners() {
+    let logs = "test api::login ... ok\ntest api::logout ... FAILED\n--- FAIL: TestSync (0.20s)\ntests/test_db.py::test_insert PASSED\nFAILED tests/test_db.py::test_delete - AssertionError";
+
+    assert_eq!(test_outcomes(logs), vec![
+        TestOutcome { name: String::from("api::login"), passed: true },
+        TestOutcome { name: String::from("api::logout"), passed: false },
+        TestOutcome { name: String::from("TestSync"), passed: false },
+        TestOutcome { name: String::from("tests/test_db.py::test_insert"), passed: true },
+        TestOutcome { name: String::from("tests/test_db.py::test_delete"), passed: false },
+    ]);
+}
+
+#[test]
+fn test_flaky_when_flipping_on_same_sha() {
+    let mut history = TestHistory::default();
+    history.record_test("api::login", 1, 1, "abc", true);
+    history.record_test("api::login", 2, 1, "abc", false);
+    history.record_test("api::logout", 1, 1, "abc", true);
+    history.record_test("api::logout", 3, 1, "def", false);
+    history.record_test("api::logout", 3, 1, "def", true);
+
+    assert!(history.is_flaky("api::login"));
+    // a test broken by a new commit is not flaky, and a repeated run is only recorded once
+    assert!(!history.is_flaky("api::logout"));
+    assert_eq!(history.flaky_tests(), vec![String::from("api::login")]);
+
+    // a rerun of the failed jobs keeps the run id
+    history.record_test("api::logout", 3, 2, "def", true);
+    assert!(history.is_flaky("api::logout"));
+}
+
+#[test]
+fn test_retried_run_is_recorded_again() {
+    let mut history = TestHistory::default();
+    history.record_run(1, 1, "abc", Some(String::from("infrastructure"))).retried = true;
+    assert!(history.contains_run(1, 1));
+    assert!(!history.contains_run(1, 2));
+
+    history.record_run(1, 2, "abc", None);
+    assert!(history.contains_run(1, 2));
+    assert!(!history.runs[&1].retried);
+}
@@ -0,0 +1,202 @@
+use std::io::{Cursor, Write};
+use tool_executor::github_interaction::{
+    github_api_client::{fetch_workflow_logs, list_org_repositories, list_workflow_runs},
+    github_artifacts::{download_artifact, ArtifactRefused},
+    github_structs::Artifact, github_app_auth::GithubAppAuth, github_error::GithubError, github_http_client::GithubClient,
+    github_reporting::{find_analysis_comment, run_marker}
+};
+use wiremock::{
+    matchers::{header, header_regex, method, path, query_param}, Mock, MockServer, ResponseTemplate
+};
+use zip::{
+    write::SimpleFileOptions, ZipWriter
+};
+
+const RUNS_BODY: &str = r#"{"workflow_runs": [{"id": 101, "status": "completed", "conclusion": "failure", "head_sha": "abc123"}]}"#;
+
+#[tokio::test]
+async fn test_list_workflow_runs_from_custom_api_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/repos/owner/repo/actions/runs"))
+        .and(header("authorization", "Bearer token"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(RUNS_BODY, "application/json"))
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&format!("{}/api/v3/", server.uri()), "token");
+    let response = list_workflow_runs(&client, "owner", "repo").await.unwrap();
+
+    assert_eq!(response.workflow_runs[0].id, 101);
+    assert_eq!(response.workflow_runs[0].head_sha, "abc123");
+}
+
+#[tokio::test]
+async fn test_workflow_logs_with_invalid_utf8_are_kept() {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("0_build.txt", SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"binary output \xff\xfe\n").unwrap();
+    zip.start_file("1_test.txt", SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"error: test failed\n").unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/101/logs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(archive, "application/zip"))
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let logs = fetch_workflow_logs(&client, "owner", "repo", 101).await.unwrap();
+
+    assert_eq!(logs, "binary output \u{fffd}\u{fffd}\nerror: test failed\n");
+}
+
+#[tokio::test]
+async fn test_artifacts_are_unpacked_within_limits() {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("reports/junit.xml", SimpleFileOptions::default()).unwrap();
+    zip.write_all(&[b'x'; 600]).unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/artifacts/7/zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(archive, "application/zip"))
+        .mount(&server)
+        .await;
+    let client = GithubClient::new(&server.uri(), "token");
+    let dir = tempfile::tempdir().unwrap();
+    let artifact = |name: &str| Artifact { id: 7, name: name.to_string(), size_in_bytes: 100, expired: false };
+
+    let files = download_artifact(&client, "owner", "repo", &artifact("test-results"), dir.path(), 1000).await.unwrap();
+    assert_eq!(files, vec![dir.path().join("test-results/reports/junit.xml")]);
+
+    // the compressed size fits, the unpacked one does not, nothing is left behind
+    let error = download_artifact(&client, "owner", "repo", &artifact("coverage"), dir.path(), 500).await.unwrap_err();
+    assert!(error.is::<ArtifactRefused>());
+    assert_eq!(error.to_string(), "Artifact 'coverage' unpacks to more than 500 bytes");
+    assert!(!dir.path().join("coverage").exists());
+
+    for name in [".", ".."] {
+        let error = download_artifact(&client, "owner", "repo", &artifact(name), dir.path(), 1000).await.unwrap_err();
+        assert!(error.is::<ArtifactRefused>(), "{}", error);
+    }
+}
+
+#[tokio::test]
+async fn test_not_modified_response_is_served_from_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(200).insert_header("etag", "\"v1\"").set_body_raw(RUNS_BODY, "application/json"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let first = list_workflow_runs(&client, "owner", "repo").await.unwrap();
+    let second = list_workflow_runs(&client, "owner", "repo").await.unwrap();
+
+    assert_eq!(first.workflow_runs[0].id, second.workflow_runs[0].id);
+}
+
+#[tokio::test]
+async fn test_rate_limited_response_returns_typed_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(403)
+            .insert_header("x-ratelimit-remaining", "0")
+            .insert_header("x-ratelimit-reset", "99999999999")
+            .set_body_raw(r#"{"message": "API rate limit exceeded"}"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let error = list_workflow_runs(&client, "owner", "repo").await.unwrap_err();
+
+    assert!(matches!(error, GithubError::RateLimited { reset_at: 99999999999 }));
+}
+
+#[tokio::test]
+async fn test_github_app_installation_token_is_cached() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/app/installations/42/access_tokens"))
+        .and(header_regex("authorization", "^Bearer ey"))
+        .respond_with(ResponseTemplate::new(201)
+            .set_body_raw(r#"{"token": "ghs_installation", "expires_at": "2999-01-01T00:00:00Z"}"#, "application/json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .and(header("authorization", "Bearer ghs_installation"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(RUNS_BODY, "application/json"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let app = GithubAppAuth::new("1234", include_bytes!("../fixtures/github_app_test_key.pem"), 42).unwrap();
+    let client = GithubClient::with_app(&server.uri(), app);
+
+    list_workflow_runs(&client, "owner", "repo").await.unwrap();
+    list_workflow_runs(&client, "owner", "repo").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_org_repositories_skip_archived() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/orgs/corp/repos"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"[
+            {"name": "api", "owner": {"login": "corp"}, "archived": false},
+            {"name": "legacy", "owner": {"login": "corp"}, "archived": true}
+        ]"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let repositories = list_org_repositories(&client, "corp").await.unwrap();
+
+    assert_eq!(repositories.len(), 1);
+    assert_eq!(repositories[0].name, "api");
+}
+
+#[tokio::test]
+async fn test_analysis_comment_is_found_on_a_later_page() {
+    let server = MockServer::start().await;
+    let comments_path = "/repos/owner/repo/issues/7/comments";
+    Mock::given(method("GET"))
+        .and(path(comments_path))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            format!(r#"[{{"id": 2, "body": "lgtm"}}, {{"id": 3, "body": "{} analysis"}}]"#, run_marker(101)),
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(comments_path))
+        .and(query_param("per_page", "100"))
+        .respond_with(ResponseTemplate::new(200)
+            .insert_header("link", format!(r#"<{}{}?per_page=100&page=2>; rel="next", <{}{}?per_page=100&page=2>; rel="last""#, server.uri(), comments_path, server.uri(), comments_path))
+            .set_body_raw(r#"[{"id": 1, "body": "first"}]"#, "application/json"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    let client = GithubClient::new(&server.uri(), "token");
+    let comment = find_analysis_comment(&client, "owner", "repo", 7).await.unwrap();
+
+    assert_eq!(comment.map(|comment| comment.id), Some(3));
+}
@@ -1,15 +1,51 @@
+#[cfg(test)]
+mod ci_providers;
+#[cfg(test)]
+mod dag;
+#[cfg(test)]
+mod duration_history;
+#[cfg(test)]
+mod error_extraction;
+#[cfg(test)]
+mod failure_classification;
+#[cfg(test)]
+mod github_client;
+#[cfg(test)]
+mod local_git;
+#[cfg(test)]
+mod log_diff;
+#[cfg(test)]
+mod log_parser;
+#[cfg(test)]
+mod log_reader;
+#[cfg(test)]
+mod pipeline;
+#[cfg(test)]
+mod process_execution;
+#[cfg(test)]
+mod report_files;
+#[cfg(test)]
+mod test_reports;
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::error::Error;
     use std::path::PathBuf;
-    use tokio;
-    use tool_executor::github_interaction::github_structs::{WorkflowRun, WorkflowRunsResponse};
+    use agent_core::agent_structs::Repository;
+    use memory_store::failure_signatures::FailureSignatures;
+    use tool_executor::github_interaction::github_commits::{find_regression_range, RegressionRange};
+    use tool_executor::github_interaction::github_issues::{failure_signature, step_signature};
+    use tool_executor::github_interaction::github_jobs::failed_step_names;
+    use tool_executor::github_interaction::github_reporting::{format_analysis_comment, run_marker, runs_to_report};
+    use tool_executor::github_interaction::github_structs::{Job, JobStep, WorkflowRun, WorkflowRunsResponse};
+    use tool_executor::github_interaction::github_webhooks::{triggered_repository, verify_signature};
 
     fn mock_workflow_runs() -> WorkflowRunsResponse {
         WorkflowRunsResponse {
             workflow_runs: vec![
-                WorkflowRun { id: 101, status: "completed".into(), conclusion: Some("success".into()) },
-                WorkflowRun { id: 102, status: "in_progress".into(), conclusion: None },
+                WorkflowRun { id: 101, status: "completed".into(), conclusion: Some("success".into()), ..Default::default() },
+                WorkflowRun { id: 102, status: "in_progress".into(), conclusion: None, ..Default::default() },
             ],
         }
     }
@@ -99,4 +135,166 @@ mod tests {
         let summary = result.unwrap();
         assert!(summary.contains("Found 1 error"));
     }
+
+    #[test]
+    fn test_analysis_comment_is_marked_with_run_id() {
+        let run = WorkflowRun {
+            id: 103,
+            status: "completed".into(),
+            conclusion: Some("failure".into()),
+            name: Some("main_ci".into()),
+            head_sha: "abc123".into(),
+            ..Default::default()
+        };
+
+        let comment = format_analysis_comment(&run, "- build failed");
+
+        assert!(comment.starts_with(&run_marker(103)));
+        assert!(!comment.contains(&run_marker(101)));
+        assert!(comment.contains("main_ci"));
+        assert!(comment.contains("- build failed"));
+    }
+
+    #[test]
+    fn test_only_the_latest_commit_of_a_pull_request_is_reported() {
+        fn run(id: u64, name: &str, conclusion: &str, sha: &str) -> WorkflowRun {
+            WorkflowRun {
+                id,
+                status: "completed".into(),
+                conclusion: Some(conclusion.into()),
+                name: Some(name.into()),
+                head_sha: sha.into(),
+                ..Default::default()
+            }
+        }
+
+        // newest first: pull request 1 was fixed by a later push, on pull request 2 the rerun of ci
+        // passed while lint still fails, pull request 3 failed on its latest commit
+        let runs = vec![
+            run(9, "ci", "success", "b2"),
+            run(8, "ci", "success", "d4"),
+            run(7, "lint", "failure", "d4"),
+            run(6, "ci", "failure", "d4"),
+            run(5, "ci", "failure", "a1"),
+            run(4, "ci", "failure", "e5"),
+            run(3, "ci", "failure", "e5"),
+        ];
+        let pr_numbers = HashMap::from([(9, 1), (8, 2), (7, 2), (6, 2), (5, 1), (4, 3), (3, 3)]);
+
+        let reported = runs_to_report(&runs, &pr_numbers).into_iter().map(|(run, pr)| (run.id, pr)).collect::<Vec<(u64, u64)>>();
+        assert_eq!(reported, vec![(7, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn test_repositories_keep_their_workflow_logs_apart() {
+        let repository = Repository::parse_namespaced("owner/repo").unwrap();
+        assert_eq!(repository.workflows_log(), PathBuf::from("logs/owner/repo/gh_workflows.log"));
+        assert_eq!(Repository::new("owner", "repo").workflows_log(), PathBuf::from("logs/gh_workflows.log"));
+        assert!(Repository::parse_namespaced("owner").is_none());
+        assert!(Repository::parse_namespaced("/repo").is_none());
+    }
+
+    #[test]
+    fn test_failure_signature_ignores_timestamps_and_numbers() {
+        let first = "2025-10-01T10:00:00.1234567Z ##[error]Process completed with exit code 101.\n";
+        let second = "2025-10-02T11:30:00.7654321Z ##[error]Process completed with exit code 102.\n";
+        let other = "2025-10-02T11:30:00.7654321Z ##[error]Unable to resolve action\n";
+
+        assert_eq!(failure_signature("main_ci", first), failure_signature("main_ci", second));
+        assert_ne!(failure_signature("main_ci", first), failure_signature("main_ci", other));
+        assert_ne!(failure_signature("main_ci", first), failure_signature("release", first));
+        assert!(failure_signature("main_ci", first).is_some());
+    }
+
+    #[test]
+    fn test_failures_without_error_lines_are_signed_by_their_failed_steps() {
+        fn job(name: &str, conclusion: &str, steps: &[(&str, &str)]) -> Job {
+            Job {
+                name: name.into(),
+                conclusion: Some(conclusion.into()),
+                steps: steps.iter().map(|(name, conclusion)| JobStep { name: (*name).into(), conclusion: Some((*conclusion).into()), ..Default::default() }).collect(),
+                ..Default::default()
+            }
+        }
+
+        let logs = "2025-10-01T10:00:00.1234567Z Run ./deploy.sh\n2025-10-01T10:00:01.1234567Z Process exited\n";
+        assert_eq!(failure_signature("main_ci", logs), None);
+
+        let jobs = vec![
+            job("build", "failure", &[("Checkout", "success"), ("Run tests", "failure")]),
+            job("lint", "success", &[("Clippy", "success")]),
+            job("deploy", "failure", &[]),
+        ];
+        let failed_steps = failed_step_names(&jobs);
+        assert_eq!(failed_steps, vec!["build / Run tests", "deploy"]);
+        assert!(step_signature("main_ci", &failed_steps).is_some());
+        assert_ne!(step_signature("main_ci", &failed_steps), step_signature("main_ci", &failed_steps[1..]));
+        assert_eq!(step_signature("main_ci", &[]), None);
+    }
+
+    #[test]
+    fn test_recurring_failure_reported_once_threshold_is_reached() {
+        let mut signatures = FailureSignatures::default();
+        signatures.record("abc", "main_ci", 1, "https://github.com/run/1");
+        signatures.record("abc", "main_ci", 1, "https://github.com/run/1");
+        signatures.record("abc", "main_ci", 2, "https://github.com/run/2");
+        assert!(signatures.pending_reports(3).is_empty());
+
+        signatures.record("abc", "main_ci", 3, "https://github.com/run/3");
+        assert_eq!(signatures.pending_reports(3), vec!["abc".to_string()]);
+
+        signatures.signatures.get_mut("abc").unwrap().reported_occurrences = 3;
+        assert!(signatures.pending_reports(3).is_empty());
+        assert!(!signatures.contains_run(2, 1));
+        signatures.mark_checked(2, 1);
+        assert!(signatures.contains_run(2, 1));
+        assert!(!signatures.contains_run(2, 2));
+    }
+
+    #[test]
+    fn test_regression_range_spans_last_green_to_first_red() {
+        fn run(id: u64, conclusion: &str, sha: &str, branch: &str) -> WorkflowRun {
+            WorkflowRun {
+                id,
+                status: "completed".into(),
+                conclusion: Some(conclusion.into()),
+                name: Some("main_ci".into()),
+                head_sha: sha.into(),
+                head_branch: Some(branch.into()),
+                ..Default::default()
+            }
+        }
+
+        // newest first, as github lists them
+        let runs = vec![
+            run(5, "failure", "e5", "main"),
+            run(4, "success", "d4", "feature"),
+            run(3, "cancelled", "c3", "main"),
+            run(2, "failure", "b2", "main"),
+            run(1, "success", "a1", "main"),
+        ];
+
+        assert_eq!(find_regression_range(&runs, &runs[0]), Some(RegressionRange {
+            last_green_sha: Some("a1".into()),
+            first_red_sha: "b2".into(),
+        }));
+        assert_eq!(find_regression_range(&runs, &runs[1]), None);
+    }
+
+    #[test]
+    fn test_webhook_signature_and_event_filtering() {
+        // example from the github webhook documentation
+        let secret = b"It's a Secret to Everybody";
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert!(verify_signature(secret, b"Hello, World!", signature));
+        assert!(!verify_signature(secret, b"Hello, World?", signature));
+        assert!(!verify_signature(b"another secret", b"Hello, World!", signature));
+
+        let completed = br#"{"action": "completed", "repository": {"full_name": "owner/repo"}}"#;
+        let requested = br#"{"action": "requested", "repository": {"full_name": "owner/repo"}}"#;
+        assert_eq!(triggered_repository("workflow_run", completed).unwrap(), Some("owner/repo".to_string()));
+        assert_eq!(triggered_repository("workflow_run", requested).unwrap(), None);
+        assert_eq!(triggered_repository("push", completed).unwrap(), None);
+        assert!(triggered_repository("check_suite", b"not json").is_err());
+    }
 }
@@ -0,0 +1,62 @@
+use std::{
+    fs, path::Path, process::Command
+};
+use tool_executor::local_git::{
+    blame_line, current_branch, diff_refs, recent_commits, source_locations
+};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Jane Doe", "-c", "user.email=jane@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn checkout() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    fs::write(dir.path().join("lib.rs"), "fn add() {}\n").unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Add lib"]);
+    fs::write(dir.path().join("lib.rs"), "fn add() {}\nassert_eq!(add(), 3);\n").unwrap();
+    git(dir.path(), &["commit", "-q", "-am", "Break the test"]);
+    dir
+}
+
+#[tokio::test]
+async fn test_local_git_branch_and_commits() {
+    let dir = checkout();
+
+    let commits = recent_commits(dir.path(), 5).await.unwrap();
+
+    assert_eq!(current_branch(dir.path()).await.unwrap(), "main");
+    assert_eq!(commits.iter().map(|commit| commit.subject.as_str()).collect::<Vec<&str>>(), vec!["Break the test", "Add lib"]);
+    assert_eq!(commits[0].author, "Jane Doe");
+}
+
+#[tokio::test]
+async fn test_local_git_blame_and_diff() {
+    let dir = checkout();
+
+    let blame = blame_line(dir.path(), "lib.rs", 2).await.unwrap();
+    let diff = diff_refs(dir.path(), "HEAD~1", "HEAD").await.unwrap();
+
+    assert_eq!((blame.author.as_str(), blame.summary.as_str()), ("Jane Doe", "Break the test"));
+    assert_eq!(blame.content, "assert_eq!(add(), 3);");
+    assert!(diff.contains("+assert_eq!(add(), 3);"));
+    assert!(diff_refs(dir.path(), "--output=/tmp/x", "HEAD").await.is_err());
+}
+
+#[test]
+fn test_source_locations_in_logs() {
+    let logs = "thread 'tests::add' panicked at src/lib.rs:12:5:\n  --> ./crates/app/src/main.rs:3:1\nsee https://example.com:443 or (src/lib.rs:12) at 10:30:00";
+
+    assert_eq!(source_locations(logs), vec![
+        (String::from("src/lib.rs"), 12),
+        (String::from("crates/app/src/main.rs"), 3),
+    ]);
+}
@@ -0,0 +1,68 @@
+use agent_core::wrappers::{
+    runs_to_compare, RunFilter
+};
+use tool_executor::{
+    ci_providers::CiRun, log_diff::{diff_logs, normalize_line}
+};
+
+const PASSING: &str = "2024-05-01T10:00:00.0000000Z Run cargo test
+2024-05-01T10:00:01.0000000Z Downloaded 112 crates (10.2 MB) in 3.21s
+2024-05-01T10:00:02.0000000Z Started container 4f2a9c1d8e at 10:00:02
+2024-05-01T10:00:03.0000000Z test api::login ... ok
+2024-05-01T10:00:04.0000000Z test result: ok. 1 passed; finished in 0.52s";
+
+const FAILING: &str = "2024-05-02T11:00:00.0000000Z Run cargo test
+2024-05-02T11:00:01.0000000Z Downloaded 112 crates (10.2 MB) in 5.87s
+2024-05-02T11:00:02.0000000Z Started container 9b7e3f0a21 at 11:00:02
+2024-05-02T11:00:03.0000000Z test api::login ... FAILED
+2024-05-02T11:00:03.5000000Z thread 'api::login' panicked at src/api.rs:12:5
+2024-05-02T11:00:04.0000000Z test result: FAILED. 0 passed; 1 failed; finished in 0.61s";
+
+#[test]
+fn test_normalize_masks_run_specific_values() {
+    assert_eq!(
+        normalize_line("job 1234567 took 3.2s at 10:00:02 (sha 4f2a9c1d8e), exit code 1"),
+        "job <id> took <duration> at <time> (sha <id>), exit code 1"
+    );
+}
+
+#[test]
+fn test_diff_keeps_only_divergent_lines() {
+    let hunks = diff_logs(PASSING, FAILING);
+
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].start_line, 4);
+    assert_eq!(hunks[0].lines, vec![
+        "test api::login ... FAILED",
+        "thread 'api::login' panicked at src/api.rs:12:5",
+        "test result: FAILED. 0 passed; 1 failed; finished in 0.61s",
+    ]);
+}
+
+#[test]
+fn test_failed_runs_are_compared_with_passing_runs_outside_the_filter() {
+    let run = |id: u64, name: &str, conclusion: &str| CiRun {
+        id,
+        attempt: 1,
+        status: String::from("completed"),
+        conclusion: Some(conclusion.to_string()),
+        name: Some(name.to_string()),
+        branch: Some(String::from("main")),
+        ..Default::default()
+    };
+    // newest first
+    let runs = vec![
+        run(5, "CI", "failure"),
+        run(4, "Lint", "failure"),
+        run(3, "CI", "failure"),
+        run(2, "CI", "success"),
+        run(1, "Lint", "success"),
+    ];
+    let ids = |pairs: Vec<(&CiRun, &CiRun)>| pairs.iter().map(|(failed, passing)| (failed.id, passing.id)).collect::<Vec<(u64, u64)>>();
+
+    // run_ids: ${steps.list_workflows.failed_run_ids}
+    let filter = RunFilter { run_ids: Some(vec![5, 4, 3]), ..Default::default() };
+    assert_eq!(ids(runs_to_compare(&runs, &filter)), vec![(5, 2), (4, 1)]);
+    let filter = RunFilter { workflow: Some(String::from("Lint")), limit: Some(1), ..Default::default() };
+    assert_eq!(ids(runs_to_compare(&runs, &filter)), vec![(4, 1)]);
+}
@@ -0,0 +1,51 @@
+use tool_executor::log_parser::{
+    annotations, clean_log, parse_groups, parse_line, strip_ansi, Annotation, AnnotationLevel, LogEvent
+};
+
+const LOG: &str = "\n\n\u{1b}[38;5;9mWORKFLOW\u{1b}[39m 42\n\
+2024-05-01T10:00:00.1234567Z ##[group]Run cargo test\n\
+2024-05-01T10:00:01.0000000Z \u{1b}[1;31mtest add ... FAILED\u{1b}[0m\n\
+2024-05-01T10:00:02.0000000Z ::error file=src/lib.rs,line=12,col=5,title=add%3A failed::assertion failed%0Aleft: 2\n\
+2024-05-01T10:00:03.0000000Z ##[endgroup]\n\
+2024-05-01T10:00:04.0000000Z ##[warning]Node.js 16 actions are deprecated\n";
+
+#[test]
+fn test_strip_ansi_sequences() {
+    assert_eq!(strip_ansi("\u{1b}[1;31mred\u{1b}[0m \u{1b}]8;;https://x\u{7}link\u{1b}]8;;\u{1b}\\"), "red link");
+}
+
+#[test]
+fn test_parse_workflow_command_annotation() {
+    let event = parse_line("::warning file=app.js,line=1::Missing semicolon");
+
+    assert_eq!(event, LogEvent::Annotation(Annotation {
+        level: AnnotationLevel::Warning,
+        message: String::from("Missing semicolon"),
+        title: None,
+        file: Some(String::from("app.js")),
+        line: Some(1),
+        column: None,
+    }));
+    assert_eq!(parse_line("::set-output name=x::y"), LogEvent::Line(String::from("::set-output name=x::y")));
+}
+
+#[test]
+fn test_parse_groups_and_annotations() {
+    let groups = parse_groups(LOG);
+    let annotations = annotations(LOG);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!((groups[0].run_id, groups[0].name.as_deref()), (Some(42), Some("Run cargo test")));
+    assert_eq!(groups[0].lines, vec!["test add ... FAILED"]);
+    assert_eq!(groups[0].annotations[0].to_string(), "error src/lib.rs:12:5 [add: failed]: assertion failed\nleft: 2");
+    assert_eq!((groups[1].name.as_deref(), groups[1].annotations[0].level), (None, AnnotationLevel::Warning));
+    assert_eq!(annotations.len(), 2);
+}
+
+#[test]
+fn test_clean_log() {
+    let cleaned = clean_log(LOG);
+
+    assert!(cleaned.contains("WORKFLOW 42\nRun cargo test\ntest add ... FAILED\nerror src/lib.rs:12:5"));
+    assert!(!cleaned.contains('\u{1b}') && !cleaned.contains("2024-05-01") && !cleaned.contains("##["));
+}
@@ -0,0 +1,52 @@
+use flate2::{
+    write::GzEncoder, Compression
+};
+use std::{
+    fs, io::Write
+};
+use tool_executor::log_reader::{
+    read_log, LogSlice
+};
+
+const LOG: &[u8] = b"step 1\nstep 2\nerror: \xff broken\nstep 4\n";
+
+fn log_files() -> (tempfile::TempDir, Vec<std::path::PathBuf>) {
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("run.log");
+    let gzip = dir.path().join("run.log.gz");
+    fs::write(&plain, LOG).unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(LOG).unwrap();
+    fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+    (dir, vec![plain, gzip])
+}
+
+#[tokio::test]
+async fn test_log_tail_lines_is_lossy() {
+    let (_dir, files) = log_files();
+
+    for file in files {
+        let tail = read_log(&file, LogSlice::TailLines(2)).await.unwrap();
+        assert_eq!(tail, "error: \u{fffd} broken\nstep 4\n", "{:?}", file);
+    }
+}
+
+#[tokio::test]
+async fn test_log_tail_bytes_starts_at_line() {
+    let (_dir, files) = log_files();
+
+    for file in files {
+        let tail = read_log(&file, LogSlice::TailBytes(10)).await.unwrap();
+        assert_eq!(tail, "step 4\n", "{:?}", file);
+    }
+}
+
+#[tokio::test]
+async fn test_log_byte_range() {
+    let (_dir, files) = log_files();
+
+    for file in files {
+        let range = read_log(&file, LogSlice::Bytes { start: 7, len: 6 }).await.unwrap();
+        assert_eq!(range, "step 2", "{:?}", file);
+    }
+}
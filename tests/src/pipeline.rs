@@ -0,0 +1,230 @@
+use std::time::Duration;
+use tool_executor::process_execution::command::split_command;
+use agent_core::{
+    agent_structs::{
+        Condition, ToolOutput
+    },
+    pipeline::{
+        parse_pipeline_file, PipelineFormat
+    },
+    tool_args::{
+        parse_pipeline, ArgValue, StepOutputs, ToolArgs
+    }
+};
+
+const YAML_PIPELINE: &str = "steps:
+  - tool: list_workflows
+    args:
+      branch: main
+      limit: 20
+  - id: classify
+    tool: classify_failures
+    when: runs_failed
+    args:
+      retry: true
+    timeout_secs: 600
+    retries: 2
+    on_failure:
+      - tool: analize_agent_logs
+  - tool: track_durations
+    args: { threshold_percent: 25 }
+";
+
+const TOML_PIPELINE: &str = r#"[[steps]]
+tool = "list_workflows"
+args = { branch = "main", limit = 20 }
+
+[[steps]]
+id = "classify"
+tool = "classify_failures"
+when = "runs_failed"
+args = { retry = true }
+timeout_secs = 600
+retries = 2
+
+[[steps.on_failure]]
+tool = "analize_agent_logs"
+
+[[steps]]
+tool = "track_durations"
+args = { threshold_percent = 25 }
+"#;
+
+#[test]
+fn test_pipeline_steps_carry_typed_arguments() {
+    let steps = parse_pipeline(r#"list_workflows branch=main limit=20
+        run_command command="cargo test --workspace" classify_failures retry=true track_durations threshold_percent=25.5"#).unwrap();
+
+    let names = steps.iter().map(|step| step.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names, vec!["list_workflows", "run_command", "classify_failures", "track_durations"]);
+    assert_eq!(steps[0].args.text("branch"), Some("main"));
+    assert_eq!(steps[0].args.integer("limit"), Some(20));
+    assert_eq!(steps[0].args.text("workflow"), None);
+    assert_eq!(steps[1].args.text("command"), Some("cargo test --workspace"));
+    assert_eq!(steps[2].args.flag("retry"), Some(true));
+    assert_eq!(steps[3].args.number("threshold_percent"), Some(25.5));
+}
+
+#[test]
+fn test_literal_values_are_not_taken_for_references() {
+    let mut args = ToolArgs::default();
+    args.set_literal("notify", "message", "run_command failed: echo ${HOME}").unwrap();
+    assert_eq!(args.text("message"), Some("run_command failed: echo ${HOME}"));
+    assert!(ToolArgs::parse("notify", &[String::from("message=echo ${HOME}")]).is_err());
+
+    let steps = parse_pipeline("analize_gh_workflows_logs checkout=/src/repo max_bytes=1024").unwrap();
+    assert_eq!(steps[0].args.text("checkout"), Some("/src/repo"));
+}
+
+#[test]
+fn test_pipeline_values_keep_their_inner_quotes() {
+    let steps = parse_pipeline(r#"run_command command="git commit -m \"fix it\" --author=\\me" list_workflows branch=say"hi""#).unwrap();
+
+    assert_eq!(steps[0].args.text("command"), Some(r#"git commit -m "fix it" --author=\me"#));
+    assert_eq!(split_command(steps[0].args.text("command").unwrap()).unwrap(), ["git", "commit", "-m", "fix it", "--author=me"]);
+    // only quotes enclosing the whole value are removed
+    assert_eq!(steps[1].args.text("branch"), Some(r#"say"hi""#));
+}
+
+#[test]
+fn test_invalid_pipelines_are_rejected() {
+    let cases = [
+        ("list_workflows limit=many", "Step 1 'list_workflows': Invalid value 'many' of argument 'limit', expected a non-negative integer"),
+        ("notify list_workflows brnch=main", "Step 2 'list_workflows': Unknown argument 'brnch' of tool 'list_workflows', expected one of: branch, workflow, limit, run_ids"),
+        ("git_blame file=src/main.rs", "Step 1 'git_blame': Tool 'git_blame' requires the 'line' argument"),
+        ("deploy_everything", "Step 1 'deploy_everything': Tool 'deploy_everything' not recognized"),
+        ("limit=5 list_workflows", "Argument 'limit=5' is not preceded by a step"),
+        ("run_command command=\"cargo test", "Unterminated quote in the pipeline"),
+        (r#"run_command command="cargo test\""#, "Unterminated quote in the pipeline"),
+    ];
+
+    for (pipeline, error) in cases {
+        assert_eq!(parse_pipeline(pipeline).unwrap_err().to_string(), error);
+    }
+    assert!(ToolArgs::parse("classify_failures", &[String::from("retry=yes")]).is_err());
+}
+
+#[test]
+fn test_yaml_and_toml_pipeline_files_describe_the_same_steps() {
+    for (content, format) in [(YAML_PIPELINE, PipelineFormat::Yaml), (TOML_PIPELINE, PipelineFormat::Toml)] {
+        let steps = parse_pipeline_file(content, format).unwrap();
+
+        let ids = steps.iter().map(|step| step.id.as_str()).collect::<Vec<&str>>();
+        assert_eq!(ids, vec!["list_workflows", "classify", "track_durations"], "{:?}", format);
+        assert_eq!(steps[0].args.integer("limit"), Some(20));
+        assert_eq!(steps[0].when, Condition::Always);
+        assert_eq!(steps[1].name, "classify_failures");
+        assert_eq!(steps[1].when, Condition::RunsFailed);
+        assert_eq!(steps[1].args.flag("retry"), Some(true));
+        assert_eq!(steps[1].timeout, Some(Duration::from_secs(600)));
+        assert_eq!(steps[1].retries, 2);
+        assert_eq!(steps[1].on_failure[0].name, "analize_agent_logs");
+        assert_eq!(steps[2].args.number("threshold_percent"), Some(25.0));
+    }
+}
+
+#[test]
+fn test_handler_ids_are_scoped_to_their_step() {
+    // the tool of a handler can run again as a step of its own, and in another handler
+    let yaml = YAML_PIPELINE.replace("      - tool: analize_agent_logs\n", "      - tool: analize_agent_logs\n      - tool: analize_agent_logs\n")
+        + "  - tool: analize_agent_logs\n    on_failure:\n      - id: notify\n        tool: analize_agent_logs\n";
+    let steps = parse_pipeline_file(&yaml, PipelineFormat::Yaml).unwrap();
+
+    let handler_ids = steps[1].on_failure.iter().map(|handler| handler.id.as_str()).collect::<Vec<&str>>();
+    assert_eq!(handler_ids, vec!["classify.on_failure.1", "classify.on_failure.2"]);
+    assert_eq!(steps[3].id, "analize_agent_logs");
+    assert_eq!(steps[3].on_failure[0].id, "notify");
+
+    let yaml = yaml.replace("      - tool: analize_agent_logs\n      - tool", "      - id: notify\n        tool: analize_agent_logs\n      - id: notify\n        tool");
+    assert_eq!(
+        parse_pipeline_file(&yaml, PipelineFormat::Yaml).unwrap_err().to_string(),
+        "line 16: Step 2 'classify', on_failure step 2 'notify': The id 'notify' is already used by an earlier step, give the steps distinct ids"
+    );
+}
+
+#[test]
+fn test_pipeline_file_errors_point_at_the_line() {
+    let cases = [
+        (YAML_PIPELINE.replace("tool: classify_failures", "tool: classify"), PipelineFormat::Yaml, "line 7: Step 2 'classify': Tool 'classify' not recognized"),
+        (YAML_PIPELINE.replace("retry: true", "retry: often"), PipelineFormat::Yaml, "line 10: Step 2 'classify': Invalid value 'often' of argument 'retry', expected true or false"),
+        (YAML_PIPELINE.replace("- tool: analize_agent_logs", "- tool: git_blame"), PipelineFormat::Yaml, "line 14: Step 2 'classify', on_failure step 1 'git_blame': Tool 'git_blame' requires the 'file' argument"),
+        (YAML_PIPELINE.replace("id: classify", "id: list_workflows"), PipelineFormat::Yaml, "line 6: Step 2 'list_workflows': The id 'list_workflows' is already used by an earlier step, give the steps distinct ids"),
+        (TOML_PIPELINE.replace("threshold_percent = 25", "threshold = 25"), PipelineFormat::Toml, "line 18: Step 3 'track_durations': Unknown argument 'threshold' of tool 'track_durations', expected one of: window_days, threshold_percent"),
+        (TOML_PIPELINE.replace("tool = \"analize_agent_logs\"", "tool = \"analize\""), PipelineFormat::Toml, "line 14: Step 2 'classify', on_failure step 1 'analize': Tool 'analize' not recognized"),
+        // flow style values and tables without the key are located as well
+        (YAML_PIPELINE.replace("{ threshold_percent: 25 }", "{ window_days: 7,\n      threshold_percent: high }"), PipelineFormat::Yaml, "line 17: Step 3 'track_durations': Invalid value 'high' of argument 'threshold_percent', expected a number"),
+        (TOML_PIPELINE.replace("tool = \"analize_agent_logs\"", "tool = \"git_blame\""), PipelineFormat::Toml, "line 13: Step 2 'classify', on_failure step 1 'git_blame': Tool 'git_blame' requires the 'file' argument"),
+    ];
+
+    for (content, format, error) in cases {
+        assert_eq!(parse_pipeline_file(&content, format).unwrap_err().to_string(), error);
+    }
+
+    // syntax and type errors are located by the parsers themselves
+    let error = parse_pipeline_file(&YAML_PIPELINE.replace("retries: 2", "retries: twice"), PipelineFormat::Yaml).unwrap_err().to_string();
+    assert!(error.contains("line 12"), "{}", error);
+    let error = parse_pipeline_file(&TOML_PIPELINE.replace("when = ", "wen = "), PipelineFormat::Toml).unwrap_err().to_string();
+    assert!(error.contains("line 8"), "{}", error);
+}
+
+#[test]
+fn test_references_resolve_to_the_outputs_of_earlier_steps() {
+    let steps = parse_pipeline(r#"list_workflows classify_failures run_ids=${steps.list_workflows.failed_run_ids}
+        run_command command="echo ${steps.list_workflows.failed_run_ids} on ${steps.list_workflows.output}""#).unwrap();
+    assert_eq!(steps[1].args.integer_list("run_ids"), None);
+
+    let mut outputs = StepOutputs::new();
+    outputs.insert(
+        String::from("list_workflows"),
+        ToolOutput::from(String::from("2 runs")).with("failed_run_ids", ArgValue::IntegerList(vec![101, 102])),
+    );
+    let classify = steps[1].args.resolve(&steps[1].name, &outputs).unwrap();
+    assert_eq!(classify.integer_list("run_ids"), Some(&[101, 102][..]));
+    let command = steps[2].args.resolve(&steps[2].name, &outputs).unwrap();
+    assert_eq!(command.text("command"), Some("echo 101,102 on 2 runs"));
+
+    // a step that did not run leaves nothing to resolve
+    assert!(steps[1].args.resolve(&steps[1].name, &StepOutputs::new()).is_err());
+}
+
+#[test]
+fn test_invalid_references_are_rejected_at_load_time() {
+    let cases = [
+        (
+            "classify_failures run_ids=${steps.list_workflows.run_ids} list_workflows",
+            "Step 1 'classify_failures': Argument 'run_ids' refers to step 'list_workflows', which does not run before it",
+        ),
+        (
+            "list_workflows diff_failed_logs run_ids=${steps.list_workflows.issue_numbers}",
+            "Step 2 'diff_failed_logs': Argument 'run_ids' refers to 'issue_numbers', which step 'list_workflows' (list_workflows) does not publish",
+        ),
+        (
+            "list_workflows git_recent_commits limit=${steps.list_workflows.run_ids}",
+            "Step 2 'git_recent_commits': Argument 'limit' expects a non-negative integer, but steps.list_workflows.run_ids is a comma-separated list of integers",
+        ),
+        ("list_workflows limit=${list_workflows.run_ids}", "Invalid reference '${list_workflows.run_ids}', expected ${steps.<id>.<output>}"),
+    ];
+    for (pipeline, error) in cases {
+        assert!(parse_pipeline(pipeline).unwrap_err().to_string().ends_with(error), "{}", pipeline);
+    }
+
+    let yaml = format!("{}    args:\n      run_ids: ${{steps.classify.retried_ids}}\n", YAML_PIPELINE.replace("    args: { threshold_percent: 25 }\n", "").replace("track_durations", "diff_failed_logs"));
+    assert_eq!(
+        parse_pipeline_file(&yaml, PipelineFormat::Yaml).unwrap_err().to_string(),
+        "line 17: Step 3 'diff_failed_logs': Argument 'run_ids' refers to 'retried_ids', which step 'classify' (classify_failures) does not publish"
+    );
+    // the handlers only see the steps before their parent
+    let toml = TOML_PIPELINE.replace("tool = \"analize_agent_logs\"", "tool = \"analize_gh_workflows_logs\"\nargs = { run_ids = \"${steps.classify.run_ids}\" }");
+    assert_eq!(
+        parse_pipeline_file(&toml, PipelineFormat::Toml).unwrap_err().to_string(),
+        "line 15: Step 2 'classify', on_failure step 1 'analize_gh_workflows_logs': Argument 'run_ids' refers to step 'classify', which does not run before it"
+    );
+    let toml = toml.replace("steps.classify.run_ids", "steps.list_workflows.failed_run_ids");
+    assert!(parse_pipeline_file(&toml, PipelineFormat::Toml).is_ok());
+
+    // a step waits for the steps its handlers refer to
+    let yaml = YAML_PIPELINE.replace("  - tool: track_durations\n", "  - tool: track_durations\n    needs: []\n")
+        + "    on_failure:\n      - tool: analize_gh_workflows_logs\n        args: { run_ids: \"${steps.list_workflows.failed_run_ids}\" }\n";
+    let steps = parse_pipeline_file(&yaml, PipelineFormat::Yaml).unwrap();
+    assert_eq!(steps[2].needs, vec![String::from("list_workflows")]);
+}
@@ -0,0 +1,168 @@
+use std::{
+    fs, path::PathBuf, time::Duration
+};
+use tool_executor::process_execution::{
+    command::{
+        run_command, split_command, CommandPolicy
+    },
+    sandbox::{
+        SandboxDir, SandboxOptions
+    }
+};
+
+fn policy(allowed_binaries: &[&str]) -> CommandPolicy {
+    CommandPolicy {
+        allowed_binaries: allowed_binaries.iter().map(|binary| binary.to_string()).collect(),
+        allowed_env: vec![String::from("PATH")],
+        working_dir: None,
+        timeout: Duration::from_secs(5),
+        max_output_bytes: 1024,
+        sandbox: None,
+    }
+}
+
+fn sandboxed(allowed_binaries: &[&str], options: SandboxOptions) -> CommandPolicy {
+    CommandPolicy {
+        sandbox: Some(options),
+        ..policy(allowed_binaries)
+    }
+}
+
+fn sandbox_options() -> SandboxOptions {
+    SandboxOptions {
+        cpu_time: None,
+        memory_bytes: None,
+        working_dir: SandboxDir::Temp,
+        isolate_network: false,
+    }
+}
+
+fn argv(command: &str) -> Vec<String> {
+    command.split_whitespace().map(String::from).collect()
+}
+
+#[tokio::test]
+async fn test_command_not_in_allowlist_is_rejected() {
+    let result = run_command(&policy(&["echo"]), &argv("/bin/echo hello")).await;
+
+    assert!(result.unwrap_err().to_string().contains("not in COMMAND_ALLOWLIST"));
+}
+
+#[tokio::test]
+async fn test_command_arguments_are_not_interpolated() {
+    let output = run_command(&policy(&["echo"]), &argv("echo $HOME;ls")).await.unwrap();
+
+    assert!(output.success());
+    assert_eq!(output.stdout, "$HOME;ls\n");
+}
+
+#[tokio::test]
+async fn test_command_is_split_with_quotes() {
+    let argv = split_command(r#"echo -n "fix it" 'say "hi"' a\ b "" \$HOME"#).unwrap();
+    assert_eq!(argv, ["echo", "-n", "fix it", "say \"hi\"", "a b", "", "$HOME"]);
+
+    let output = run_command(&policy(&["echo"]), &argv).await.unwrap();
+    assert_eq!(output.stdout, "fix it say \"hi\" a b  $HOME");
+
+    assert_eq!(split_command("git commit -m \"fix it").unwrap_err().to_string(), "Command 'git commit -m \"fix it' has an unclosed \" quote");
+}
+
+#[tokio::test]
+async fn test_command_environment_is_filtered() {
+    let output = run_command(&policy(&["env"]), &argv("env")).await.unwrap();
+
+    assert!(output.stdout.lines().all(|line| line.starts_with("PATH=")));
+}
+
+#[tokio::test]
+async fn test_command_output_is_capped() {
+    let output = run_command(&policy(&["head"]), &argv("head -c 100000 /dev/zero")).await.unwrap();
+
+    assert!(output.success() && output.truncated);
+    assert_eq!(output.stdout.len(), 1024);
+}
+
+#[tokio::test]
+async fn test_command_is_killed_on_timeout() {
+    let policy = CommandPolicy {
+        timeout: Duration::from_millis(200),
+        ..policy(&["sleep"])
+    };
+    let output = run_command(&policy, &argv("sleep 10")).await.unwrap();
+
+    assert!(output.timed_out && !output.success());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandbox_sets_resource_limits() {
+    let options = SandboxOptions {
+        cpu_time: Some(Duration::from_secs(7)),
+        memory_bytes: Some(512 * 1024 * 1024),
+        ..sandbox_options()
+    };
+    let command = vec![String::from("sh"), String::from("-c"), String::from("ulimit -t; ulimit -v")];
+    let output = run_command(&sandboxed(&["sh"], options), &command).await.unwrap();
+
+    assert_eq!(output.stdout, "7\n524288\n");
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandbox_temp_dir_is_removed() {
+    let output = run_command(&sandboxed(&["pwd"], sandbox_options()), &argv("pwd")).await.unwrap();
+    let dir = PathBuf::from(output.stdout.trim());
+
+    assert!(dir.file_name().unwrap().to_string_lossy().starts_with("devops-agent-command-"));
+    assert!(!dir.exists());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandbox_read_only_working_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let policy = CommandPolicy {
+        working_dir: Some(dir.path().to_path_buf()),
+        ..sandboxed(&["touch"], SandboxOptions { working_dir: SandboxDir::ReadOnly, ..sandbox_options() })
+    };
+    let output = run_command(&policy, &argv("touch written")).await.unwrap();
+
+    assert!(!output.success());
+    assert!(output.stderr.contains("Read-only file system"));
+    assert!(!dir.path().join("written").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandbox_isolates_network() {
+    let options = SandboxOptions {
+        isolate_network: true,
+        ..sandbox_options()
+    };
+    let output = run_command(&sandboxed(&["cat"], options), &argv("cat /proc/net/dev")).await.unwrap();
+    let interfaces = output.stdout
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split(':').next())
+        .map(str::trim)
+        .collect::<Vec<&str>>();
+
+    assert_eq!(interfaces, vec!["lo"]);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandbox_kills_process_group_on_timeout() {
+    let policy = CommandPolicy {
+        timeout: Duration::from_millis(500),
+        ..sandboxed(&["sh"], sandbox_options())
+    };
+    let command = vec![String::from("sh"), String::from("-c"), String::from("sleep 30 & echo $!; wait")];
+    let output = run_command(&policy, &command).await.unwrap();
+    let background_pid = output.stdout.trim();
+
+    assert!(output.timed_out);
+    // the background process is gone, or at most waits to be reaped
+    let stat = fs::read_to_string(format!("/proc/{}/stat", background_pid)).unwrap_or_default();
+    assert!(stat.is_empty() || stat.contains(") Z "));
+}
@@ -0,0 +1,36 @@
+use tool_executor::report_files::{
+    summarize_report_content, ReportSummary
+};
+
+#[test]
+fn test_junit_report_lists_failed_cases() {
+    let report = r#"<?xml version="1.0"?>
+<testsuites>
+  <testsuite name="api" tests="3">
+    <testcase classname="api.users" name="creates_user"/>
+    <testcase classname="api.users" name="deletes_user">
+      <failure message="expected 204">assertion failed</failure>
+    </testcase>
+    <testcase classname="api.users" name="lists_users"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+
+    let summary = summarize_report_content("junit.xml", report).unwrap();
+
+    assert_eq!(summary, ReportSummary::JUnit {
+        tests: 3,
+        failures: 1,
+        skipped: 1,
+        failed_cases: vec!["api.users::deletes_user".to_string()],
+    });
+}
+
+#[test]
+fn test_coverage_reports_are_recognised() {
+    let lcov = "SF:src/lib.rs\nLF:10\nLH:5\nend_of_record\nSF:src/main.rs\nLF:10\nLH:10\nend_of_record\n";
+    let cobertura = r#"<?xml version="1.0"?><coverage line-rate="0.5" branch-rate="0"></coverage>"#;
+
+    assert_eq!(summarize_report_content("lcov.info", lcov), Some(ReportSummary::Coverage { format: "LCOV", line_percent: 75.0 }));
+    assert_eq!(summarize_report_content("cobertura.xml", cobertura), Some(ReportSummary::Coverage { format: "Cobertura", line_percent: 50.0 }));
+    assert_eq!(summarize_report_content("notes.txt", "hello"), None);
+}
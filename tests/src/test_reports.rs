@@ -0,0 +1,92 @@
+use tool_executor::test_reports::{
+    parse_cargo_test, parse_junit, parse_libtest_json, parse_pytest, TestStatus
+};
+
+#[test]
+fn test_junit_cases_with_suite_time_and_message() {
+    let report = r#"<?xml version="1.0"?>
+<testsuites>
+  <testsuite name="api">
+    <testcase classname="api.users" name="creates_user" time="0.25"/>
+    <testcase classname="api.users" name="deletes_user" time="1.5">
+      <failure message="expected 204">assertion failed</failure>
+    </testcase>
+    <testcase classname="api.users" name="updates_user"><error>connection refused</error></testcase>
+    <testcase classname="api.users" name="lists_users"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+
+    let report = parse_junit(report).unwrap();
+
+    assert_eq!(report.cases.len(), 4);
+    assert_eq!(report.count(TestStatus::Failed), 2);
+    assert_eq!(report.count(TestStatus::Skipped), 1);
+    let deleted = &report.cases[1];
+    assert_eq!(deleted.suite.as_deref(), Some("api"));
+    assert_eq!(deleted.name, "api.users::deletes_user");
+    assert_eq!(deleted.duration_secs, Some(1.5));
+    assert_eq!(deleted.failure_message.as_deref(), Some("expected 204"));
+    assert_eq!(report.cases[2].failure_message.as_deref(), Some("connection refused"));
+    assert!(parse_junit("<testsuite><testcase></testsuite>").is_none());
+}
+
+#[test]
+fn test_cargo_test_output_with_panic_messages() {
+    let output = "     Running unittests src/lib.rs (target/debug/deps/api-1a2b3c)
+running 3 tests
+test users::creates ... ok
+test users::deletes ... FAILED
+test users::slow ... ignored
+
+failures:
+
+---- users::deletes stdout ----
+thread 'users::deletes' panicked at src/users.rs:10:5:
+expected 204, got 500
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+failures:
+    users::deletes
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored";
+
+    let report = parse_cargo_test(output);
+
+    assert_eq!(report.cases.len(), 3);
+    let deleted = report.failed_cases().next().unwrap();
+    assert_eq!(deleted.name, "users::deletes");
+    assert_eq!(deleted.suite.as_deref(), Some("unittests src/lib.rs"));
+    assert_eq!(deleted.failure_message.as_deref(), Some("thread 'users::deletes' panicked at src/users.rs:10:5:\nexpected 204, got 500"));
+    assert_eq!(report.to_string(), "3 tests, 1 failed, 1 skipped\n  failed: users::deletes (thread 'users::deletes' panicked at src/users.rs:10:5:)");
+}
+
+#[test]
+fn test_libtest_json_events() {
+    let output = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "users::creates" }
+{ "type": "test", "name": "users::creates", "event": "ok", "exec_time": 0.01 }
+{ "type": "test", "name": "users::deletes", "event": "failed", "stdout": "thread 'users::deletes' panicked\n" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1 }"#;
+
+    let report = parse_libtest_json(output);
+
+    assert_eq!(report.cases.len(), 2);
+    assert_eq!(report.cases[0].duration_secs, Some(0.01));
+    assert_eq!(report.cases[1].status, TestStatus::Failed);
+    assert_eq!(report.cases[1].failure_message.as_deref(), Some("thread 'users::deletes' panicked"));
+}
+
+#[test]
+fn test_pytest_summary_completes_verbose_results() {
+    let output = "tests/test_db.py::test_insert PASSED [ 50%]
+tests/test_db.py::test_delete FAILED [100%]
+=========================== short test summary info ============================
+FAILED tests/test_db.py::test_delete - AssertionError: 1 != 2";
+
+    let report = parse_pytest(output);
+
+    assert_eq!(report.cases.len(), 2);
+    assert_eq!(report.cases[0].suite.as_deref(), Some("tests/test_db.py"));
+    assert_eq!(report.cases[1].status, TestStatus::Failed);
+    assert_eq!(report.cases[1].failure_message.as_deref(), Some("AssertionError: 1 != 2"));
+}
@@ -0,0 +1,76 @@
+// the tools reach GitHub through the client configured by the environment, so like parallel_stores
+// this test gets its own binary
+use std::{
+    env::set_var, io::{Cursor, Write}
+};
+use agent_core::{
+    agent_structs::{
+        Agent, AgentInput, AgentStatus, DevOpsAgent, Repository
+    },
+    pipeline::{
+        parse_pipeline_file, PipelineFormat
+    }
+};
+use memory_store::{
+    failure_signatures::FailureSignatures, test_history::TestHistory
+};
+use wiremock::{
+    matchers::{method, path}, Mock, MockServer, ResponseTemplate
+};
+use zip::{
+    write::SimpleFileOptions, ZipWriter
+};
+
+const RUNS_BODY: &str = r#"{"workflow_runs": [
+    {"id": 1, "run_attempt": 1, "name": "CI", "status": "completed", "conclusion": "failure", "head_sha": "aaa111"}
+]}"#;
+
+fn logs_archive(logs: &str) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("0_build.txt", SimpleFileOptions::default()).unwrap();
+    zip.write_all(logs.as_bytes()).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_classify_and_track_both_process_the_same_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(RUNS_BODY, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/1/logs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(logs_archive("test api::login ... FAILED\nerror: test failed, to rerun pass `--lib`\n"), "application/zip"))
+        .mount(&server)
+        .await;
+    // SAFETY: the test runs on a single thread and is the only one in this binary
+    unsafe {
+        set_var("GITHUB_API_URL", server.uri());
+        set_var("GITHUB_TOKEN", "token");
+    }
+
+    let classify = "  - tool: classify_failures\n    args: { retry: false }\n";
+    let track = "  - tool: track_recurring_failures\n";
+    // whichever tool runs first, the second one still processes the run
+    for (first, second) in [(classify, track), (track, classify)] {
+        let yaml = format!("steps:\n{}{}", first, second);
+        let data_dir = tempfile::tempdir().unwrap();
+        let repository = Repository {
+            owner: String::from("owner"),
+            name: String::from("repo"),
+            data_dir: data_dir.path().to_path_buf(),
+        };
+        let mut agent = DevOpsAgent::new(parse_pipeline_file(&yaml, PipelineFormat::Yaml).unwrap(), repository);
+
+        let result = agent.handle_input(AgentInput { message: String::new(), context: None }).await;
+
+        assert!(matches!(result.status, AgentStatus::Success), "{}", result.output);
+        let history = TestHistory::load(&data_dir.path().join("memory/test_history.json")).unwrap();
+        assert_eq!(history.runs[&1].classification.as_deref(), Some("test"), "{}", yaml);
+        let signatures = FailureSignatures::load(&data_dir.path().join("memory/failure_signatures.json")).unwrap();
+        assert!(signatures.contains_run(1, 1), "{}", yaml);
+        assert_eq!(signatures.signatures.values().map(|record| record.occurrences.len()).sum::<usize>(), 1, "{}", yaml);
+    }
+}
@@ -0,0 +1,92 @@
+// the tools reach GitHub through the client configured by the environment, which is set once here,
+// so this test gets its own binary instead of sharing the process with the tests reading the environment
+use std::{
+    env::set_var, io::{Cursor, Write}, time::Duration
+};
+use agent_core::{
+    agent_structs::{
+        Agent, AgentInput, AgentStatus, DevOpsAgent, Repository
+    },
+    pipeline::{
+        parse_pipeline_file, PipelineFormat
+    }
+};
+use memory_store::test_history::TestHistory;
+use wiremock::{
+    matchers::{method, path}, Mock, MockServer, ResponseTemplate
+};
+use zip::{
+    write::SimpleFileOptions, ZipWriter
+};
+
+const RUNS_BODY: &str = r#"{"workflow_runs": [
+    {"id": 1, "name": "CI", "status": "completed", "conclusion": "failure", "head_sha": "aaa111"},
+    {"id": 2, "name": "CI", "status": "completed", "conclusion": "failure", "head_sha": "bbb222"},
+    {"id": 3, "name": "CI", "status": "completed", "conclusion": "success", "head_sha": "ccc333"}
+]}"#;
+
+fn logs_archive(logs: &str) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("0_build.txt", SimpleFileOptions::default()).unwrap();
+    zip.write_all(logs.as_bytes()).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_parallel_steps_keep_each_others_store_updates() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(RUNS_BODY, "application/json"))
+        .mount(&server)
+        .await;
+    let failed_logs = logs_archive("test api::login ... FAILED\nerror: test failed, to rerun pass `--lib`\n");
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/1/logs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(failed_logs.clone(), "application/zip"))
+        .mount(&server)
+        .await;
+    // track_recurring_failures saves its test history well after classify_failures saved its own
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/2/logs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(failed_logs, "application/zip").set_delay(Duration::from_millis(300)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs/3/logs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(logs_archive("test result: ok\n"), "application/zip"))
+        .mount(&server)
+        .await;
+    // SAFETY: the test runs on a single thread and is the only one in this binary
+    unsafe {
+        set_var("GITHUB_API_URL", server.uri());
+        set_var("GITHUB_TOKEN", "token");
+    }
+
+    // both steps load memory/test_history.json, update it and save it back
+    let yaml = "steps:
+  - id: classify
+    tool: classify_failures
+    args: { run_ids: 3, retry: false }
+  - id: track
+    tool: track_recurring_failures
+    needs: []
+    args: { threshold: 100 }
+";
+    let data_dir = tempfile::tempdir().unwrap();
+    let repository = Repository {
+        owner: String::from("owner"),
+        name: String::from("repo"),
+        data_dir: data_dir.path().to_path_buf(),
+    };
+    let mut agent = DevOpsAgent::new(parse_pipeline_file(yaml, PipelineFormat::Yaml).unwrap(), repository);
+    agent.max_parallel_steps = 2;
+
+    let result = agent.handle_input(AgentInput { message: String::new(), context: None }).await;
+
+    assert!(matches!(result.status, AgentStatus::Success), "{}", result.output);
+    let history = TestHistory::load(&data_dir.path().join("memory/test_history.json")).unwrap();
+    assert!(history.contains_run(3, 1), "run 3 is missing from the test history");
+    let recorded_runs = history.tests["api::login"].iter().map(|result| result.run_id).collect::<Vec<u64>>();
+    assert_eq!(recorded_runs, vec![1, 2]);
+}
@@ -0,0 +1,66 @@
+// the conditions of the steps look up the runs through the client configured by the environment, so like
+// parallel_stores this test gets its own binary
+use std::{
+    env::set_var, path::PathBuf
+};
+use agent_core::{
+    agent_structs::{
+        Agent, AgentInput, AgentStatus, DevOpsAgent, Repository
+    },
+    pipeline::{
+        parse_pipeline_file, PipelineFormat
+    }
+};
+use wiremock::{
+    matchers::{method, path}, Mock, MockServer, ResponseTemplate
+};
+
+const RUNS_BODY: &str = r#"{"workflow_runs": [
+    {"id": 1, "name": "CI", "status": "completed", "conclusion": "failure", "head_sha": "aaa111", "head_branch": "main"}
+]}"#;
+
+#[tokio::test]
+async fn test_steps_taking_outputs_of_a_skipped_step_are_skipped() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/runs"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(RUNS_BODY, "application/json"))
+        .mount(&server)
+        .await;
+    // SAFETY: the test runs on a single thread and is the only one in this binary
+    unsafe {
+        set_var("GITHUB_API_URL", server.uri());
+        set_var("GITHUB_TOKEN", "token");
+    }
+
+    let yaml = "steps:
+  - id: on_green
+    tool: notify
+    when: runs_passed
+  - id: report
+    tool: notify
+    args: { message: \"green: ${steps.on_green.output}\" }
+  - id: after_report
+    tool: notify
+  - id: after_green
+    tool: notify
+    needs: [on_green]
+";
+    let repository = Repository {
+        owner: String::from("owner"),
+        name: String::from("repo"),
+        data_dir: PathBuf::from("logs"),
+    };
+    let mut agent = DevOpsAgent::new(parse_pipeline_file(yaml, PipelineFormat::Yaml).unwrap(), repository);
+
+    let result = agent.handle_input(AgentInput { message: String::new(), context: None }).await;
+
+    assert!(matches!(result.status, AgentStatus::Success), "{}", result.output);
+    let lines = result.output.lines().skip(1).collect::<Vec<&str>>();
+    assert_eq!(lines, [
+        "on_green: skipped",
+        "report (needs on_green): skipped",
+        "after_report (needs report): succeeded",
+        "after_green (needs on_green): succeeded",
+    ]);
+}